@@ -20,6 +20,12 @@ stack_top:
 global _start:function (_start.end - _start)
 _start:
 	mov esp, stack_top
+	; A null frame pointer marks the end of the chain for backtraces
+	xor ebp, ebp
+	
+	; Hand the multiboot info pointer and magic to the kernel
+	push ebx
+	push eax
 	
 	extern kernel_main
 	call kernel_main
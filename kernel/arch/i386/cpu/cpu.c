@@ -1,10 +1,97 @@
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <boottime.h>
 #include <cpu.h>
 #include <cpu/gdt.h>
 #include <cpu/idt.h>
+#include <cpu/interrupts.h>
+#include <cpu/kvm.h>
+#include <cpu/ports.h>
+#include <cpu/tsc.h>
 #include <tty/tty.h>
 
+#define EFLAGS_IF (1 << 9)
+
 void arch_init(void)
 {
+    boot_phase("gdt");
     gdt_init();
+    boot_phase("idt");
     idt_init();
-}
\ No newline at end of file
+    boot_phase("pic");
+    interrupts_init();
+    boot_phase("tsc");
+    tsc_init();
+    boot_phase("kvmclock");
+    kvmclock_init();
+}
+
+void arch_enable_interrupts(void)
+{
+    __asm__ volatile ("sti");
+}
+
+void arch_disable_interrupts(void)
+{
+    __asm__ volatile ("cli");
+}
+
+void arch_idle(void)
+{
+    __asm__ volatile ("hlt");
+}
+
+void arch_pause(void)
+{
+    __asm__ volatile ("pause");
+}
+
+void arch_halt(void)
+{
+    while (true)
+    {
+        __asm__ volatile ("cli; hlt");
+    }
+}
+
+#define KBC_STATUS 0x64
+#define KBC_INPUT_FULL 0x02
+#define KBC_RESET 0xFE
+
+void arch_reboot(void)
+{
+    arch_disable_interrupts();
+
+    // Pulse the CPU reset line through the keyboard controller, a missing one reads as always busy
+    for (int i = 0; i < 0x10000 && (inb(KBC_STATUS) & KBC_INPUT_FULL); ++i)
+    {
+        io_wait();
+    }
+    outb(KBC_STATUS, KBC_RESET);
+
+    // Without a keyboard controller, triple fault with an empty IDT
+    struct
+    {
+        uint16_t limit;
+        uint32_t base;
+    } __attribute__((packed)) null_idt = { 0, 0 };
+    __asm__ volatile ("lidt %0; int3" : : "m"(null_idt));
+
+    arch_halt();
+}
+
+bool arch_irq_save(void)
+{
+    uint32_t eflags;
+    __asm__ volatile ("pushf; pop %0; cli" : "=r"(eflags) : : "memory");
+    return eflags & EFLAGS_IF;
+}
+
+void arch_irq_restore(bool enabled)
+{
+    if (enabled)
+    {
+        __asm__ volatile ("sti" : : : "memory");
+    }
+}
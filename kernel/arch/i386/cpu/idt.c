@@ -3,10 +3,22 @@
 #include <cpu/gdt.h>
 #include <cpu/idt.h>
 
-idt_entry_t idt_entries[256];
+#define ISR_STUBS 48
+
+idt_entry_t idt_entries[IDT_ENTRIES];
 idt_ptr_t idt_ptr;
 
-void set_idt_descriptor(uint8_t interrupt, uint32_t base, uint16_t sel, uint8_t flags)
+static void (*const isr_stubs[ISR_STUBS])(void) =
+{
+    isr_0, isr_1, isr_2, isr_3, isr_4, isr_5, isr_6, isr_7,
+    isr_8, isr_9, isr_10, isr_11, isr_12, isr_13, isr_14, isr_15,
+    isr_16, isr_17, isr_18, isr_19, isr_20, isr_21, isr_22, isr_23,
+    isr_24, isr_25, isr_26, isr_27, isr_28, isr_29, isr_30, isr_31,
+    irq_0, irq_1, irq_2, irq_3, irq_4, irq_5, irq_6, irq_7,
+    irq_8, irq_9, irq_10, irq_11, irq_12, irq_13, irq_14, irq_15,
+};
+
+static void set_idt_descriptor(uint8_t interrupt, uint32_t base, uint16_t sel, uint8_t flags)
 {
     idt_entries[interrupt].offset_lo = base & 0xFFFF;
     idt_entries[interrupt].segment = sel;
@@ -15,43 +27,18 @@ void set_idt_descriptor(uint8_t interrupt, uint32_t base, uint16_t sel, uint8_t
     idt_entries[interrupt].offset_hi = (base >> 16) & 0xFFFF;
 }
 
+// The table is only written here, before it is loaded. Handlers are attached
+// afterwards through interrupt_register_handler, so delivery never sees a half
+// written gate.
 void idt_init(void)
 {
     idt_ptr.size = (sizeof(idt_entry_t) * IDT_ENTRIES) - 1;
-    idt_ptr.offset = &idt_entries;
-
-    set_idt_descriptor(0x00, *isr_0, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);
-    set_idt_descriptor(0x01, *isr_1, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x02, *isr_2, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x03, *isr_3, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x04, *isr_4, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x05, *isr_5, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x06, *isr_6, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x07, *isr_7, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x08, *isr_8, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x09, *isr_9, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x0A, *isr_10, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x0B, *isr_11, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x0C, *isr_12, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x0D, *isr_13, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x0E, *isr_14, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x0F, *isr_15, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x10, *isr_16, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);
-    set_idt_descriptor(0x11, *isr_17, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x12, *isr_18, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x13, *isr_29, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x14, *isr_20, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x15, *isr_21, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x16, *isr_22, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x17, *isr_23, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x18, *isr_24, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x19, *isr_25, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x1A, *isr_26, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x1B, *isr_27, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x1C, *isr_28, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x1D, *isr_29, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x1E, *isr_30, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);    
-    set_idt_descriptor(0x1F, *isr_31, KERNEL_DATA_SEL, IDT_PRESENT | IDT_32_BIT_INT);
+    idt_ptr.offset = (uint32_t) idt_entries;
+
+    for (int i = 0; i < ISR_STUBS; ++i)
+    {
+        set_idt_descriptor(i, (uint32_t) isr_stubs[i], KERNEL_CODE_SEL, IDT_PRESENT | IDT_32_BIT_INT);
+    }
 
     flush_idt(&idt_ptr);
-}
\ No newline at end of file
+}
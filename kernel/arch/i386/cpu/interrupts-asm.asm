@@ -53,6 +53,31 @@ isr_%1:
 	ISR_NOERRCODE 30
 	ISR_NOERRCODE 31
 	
+	%macro IRQ 2
+[global irq_%1:function]
+irq_%1:
+	push byte 0
+	push byte %2
+	jmp isr_common_stub
+	%endmacro
+	
+	IRQ 0, 32
+	IRQ 1, 33
+	IRQ 2, 34
+	IRQ 3, 35
+	IRQ 4, 36
+	IRQ 5, 37
+	IRQ 6, 38
+	IRQ 7, 39
+	IRQ 8, 40
+	IRQ 9, 41
+	IRQ 10, 42
+	IRQ 11, 43
+	IRQ 12, 44
+	IRQ 13, 45
+	IRQ 14, 46
+	IRQ 15, 47
+	
 	; Defined in interrupts.c
 	extern isr_handler
 	
@@ -1,16 +1,218 @@
+#include <stdbool.h>
+#include <stddef.h>
 #include <stdint.h>
 
+#include <cpu.h>
+#include <cpu/idt.h>
+#include <cpu/interrupts.h>
+#include <cpu/pic.h>
 #include <libk/io.h>
+#include <libk/string.h>
+#include <log.h>
+#include <panic.h>
+#include <time/time.h>
 
-typedef struct interrupt_registers_t
+#define EXCEPTION_COUNT 32
+
+// An unclaimed line firing this often per window is masked as a storm
+#define IRQ_STORM_LIMIT 1000
+#define IRQ_STORM_WINDOW_NS NS_PER_SEC
+
+static const char *exception_names[EXCEPTION_COUNT] =
+{
+    "Divide error", "Debug", "Non-maskable interrupt", "Breakpoint",
+    "Overflow", "Bound range exceeded", "Invalid opcode", "Device not available",
+    "Double fault", "Coprocessor segment overrun", "Invalid TSS", "Segment not present",
+    "Stack-segment fault", "General protection fault", "Page fault", "Reserved",
+    "x87 floating-point exception", "Alignment check", "Machine check", "SIMD floating-point exception",
+    "Virtualization exception", "Control protection exception", "Reserved", "Reserved",
+    "Reserved", "Reserved", "Reserved", "Reserved",
+    "Hypervisor injection exception", "VMM communication exception", "Security exception", "Reserved",
+};
+
+typedef struct interrupt_action_t
+{
+    interrupt_handler_t handler;
+    void *data;
+} interrupt_action_t;
+
+// Handler and data are swapped together with interrupts off, so an interrupt
+// never sees a handler paired with another driver's data
+static interrupt_action_t actions[IDT_ENTRIES];
+static interrupt_stats_t stats[IDT_ENTRIES];
+
+void interrupt_register_handler(uint8_t vector, interrupt_handler_t handler, void *data)
+{
+    bool enabled = arch_irq_save();
+    actions[vector].handler = handler;
+    actions[vector].data = data;
+    arch_irq_restore(enabled);
+}
+
+void interrupt_unregister_handler(uint8_t vector)
+{
+    interrupt_register_handler(vector, NULL, NULL);
+}
+
+void interrupt_get_stats(uint8_t vector, interrupt_stats_t *out)
+{
+    bool enabled = arch_irq_save();
+    *out = stats[vector];
+    arch_irq_restore(enabled);
+}
+
+static const char *irq_owners[IRQ_COUNT];
+
+bool irq_register_handler(uint8_t irq, interrupt_handler_t handler, void *data, const char *owner)
+{
+    if (irq >= IRQ_COUNT)
+    {
+        return false;
+    }
+
+    bool enabled = arch_irq_save();
+    bool free = !irq_owners[irq] || strcmp(irq_owners[irq], owner) == 0;
+    if (free)
+    {
+        irq_owners[irq] = owner;
+        interrupt_register_handler(IRQ_VECTOR(irq), handler, data);
+        pic_unmask(irq);
+    }
+    arch_irq_restore(enabled);
+
+    if (!free)
+    {
+        kprintf("IRQ %u is already owned by %s\n", irq, irq_owners[irq]);
+    }
+    return free;
+}
+
+void irq_free(uint8_t irq)
+{
+    if (irq >= IRQ_COUNT)
+    {
+        return;
+    }
+
+    bool enabled = arch_irq_save();
+    pic_mask(irq);
+    interrupt_unregister_handler(IRQ_VECTOR(irq));
+    irq_owners[irq] = NULL;
+    arch_irq_restore(enabled);
+}
+
+void irq_mask(uint8_t irq)
 {
-    uint32_t ds;
-    uint32_t edi, esi, ebp, useless, ebx, edx, ecs, eax;
-    uint32_t int_no, err_code;
-    uint32_t eip, cs, eflags, esp, ss;
-} interrupt_registers_t;
+    if (irq < IRQ_COUNT)
+    {
+        bool enabled = arch_irq_save();
+        pic_mask(irq);
+        arch_irq_restore(enabled);
+    }
+}
+
+void irq_unmask(uint8_t irq)
+{
+    if (irq < IRQ_COUNT)
+    {
+        bool enabled = arch_irq_save();
+        pic_unmask(irq);
+        arch_irq_restore(enabled);
+    }
+}
+
+const char *irq_owner(uint8_t irq)
+{
+    return irq < IRQ_COUNT ? irq_owners[irq] : NULL;
+}
+
+typedef struct irq_audit_t
+{
+    uint64_t window_start;
+    uint32_t window_count;
+    uint32_t spurious;
+    uint32_t unhandled;
+} irq_audit_t;
+
+static irq_audit_t irq_audit[IRQ_COUNT];
+
+uint32_t irq_spurious_count(uint8_t irq)
+{
+    return irq < IRQ_COUNT ? irq_audit[irq].spurious : 0;
+}
+
+uint32_t irq_unhandled_count(uint8_t irq)
+{
+    return irq < IRQ_COUNT ? irq_audit[irq].unhandled : 0;
+}
+
+// Logs at most once per window per line, masking the line if it keeps firing with nobody listening
+static void irq_unhandled(uint8_t irq)
+{
+    irq_audit_t *audit = &irq_audit[irq];
+    uint64_t now = time_now_ns();
+    ++audit->unhandled;
+
+    if (audit->window_count == 0 || now - audit->window_start >= IRQ_STORM_WINDOW_NS)
+    {
+        klog(LOG_WARN, "irq", "Unhandled IRQ %u (%u total)", irq, audit->unhandled);
+        audit->window_start = now;
+        audit->window_count = 0;
+    }
+
+    if (++audit->window_count >= IRQ_STORM_LIMIT)
+    {
+        pic_mask(irq);
+        klog(LOG_ERROR, "irq", "Masked IRQ %u after an interrupt storm", irq);
+        audit->window_count = 0;
+    }
+}
+
+void interrupts_init(void)
+{
+    pic_init(IRQ_BASE);
+}
 
 void isr_handler(interrupt_registers_t *regs)
 {
-    kprintf("Recieved interrupt %x\n", regs->int_no);
-}
\ No newline at end of file
+    bool is_irq = regs->int_no >= IRQ_BASE && regs->int_no < IRQ_BASE + IRQ_COUNT;
+    uint8_t irq = regs->int_no - IRQ_BASE;
+    if (is_irq && pic_spurious(irq))
+    {
+        ++irq_audit[irq].spurious;
+        return;
+    }
+
+    interrupt_action_t *action = &actions[regs->int_no];
+    if (action->handler)
+    {
+        uint64_t start = arch_cycles();
+        action->handler(regs, action->data);
+        uint64_t cycles = arch_cycles() - start;
+
+        interrupt_stats_t *stat = &stats[regs->int_no];
+        ++stat->count;
+        stat->total_cycles += cycles;
+        if (cycles > stat->max_cycles)
+        {
+            stat->max_cycles = cycles;
+        }
+    }
+    else if (regs->int_no < EXCEPTION_COUNT)
+    {
+        panic_frame(regs, "%s", exception_names[regs->int_no]);
+    }
+    else if (is_irq)
+    {
+        irq_unhandled(irq);
+    }
+    else
+    {
+        kprintf("Recieved interrupt %x\n", regs->int_no);
+    }
+
+    if (is_irq)
+    {
+        pic_eoi(irq);
+    }
+}
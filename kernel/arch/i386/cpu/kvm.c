@@ -0,0 +1,129 @@
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <cpu.h>
+#include <cpu/cpuid.h>
+#include <cpu/kvm.h>
+#include <cpu/msr.h>
+#include <libk/string.h>
+
+#define CPUID_KVM_FEATURES 0x40000001
+#define KVM_FEATURE_CLOCKSOURCE (1 << 0)
+#define KVM_FEATURE_CLOCKSOURCE2 (1 << 3)
+
+#define MSR_KVM_SYSTEM_TIME 0x12
+#define MSR_KVM_SYSTEM_TIME_NEW 0x4B564D01
+#define KVM_SYSTEM_TIME_ENABLE 1
+
+// Shared with the hypervisor, layout fixed by the KVM ABI
+typedef struct pvclock_time_info_t
+{
+    uint32_t version;
+    uint32_t pad0;
+    uint64_t tsc_timestamp;
+    uint64_t system_time;
+    uint32_t tsc_to_system_mul;
+    int8_t tsc_shift;
+    uint8_t flags;
+    uint8_t pad[2];
+} __attribute__((packed, aligned(32))) pvclock_time_info_t;
+
+static volatile pvclock_time_info_t kvmclock;
+static bool kvmclock_active;
+
+bool kvm_detect(void)
+{
+    uint32_t eax, ebx, ecx, edx;
+    cpuid(CPUID_FEATURES, 0, &eax, &ebx, &ecx, &edx);
+    if (!(ecx & CPUID_FEAT_ECX_HYPERVISOR))
+    {
+        return false;
+    }
+
+    char signature[12];
+    cpuid(CPUID_HYPERVISOR_BASE, 0, &eax, &ebx, &ecx, &edx);
+    memcpy(signature, &ebx, 4);
+    memcpy(signature + 4, &ecx, 4);
+    memcpy(signature + 8, &edx, 4);
+    return memcmp(signature, "KVMKVMKVM\0\0\0", sizeof(signature)) == 0;
+}
+
+bool kvmclock_init(void)
+{
+    if (!kvm_detect())
+    {
+        return false;
+    }
+
+    uint32_t eax, ebx, ecx, edx;
+    cpuid(CPUID_KVM_FEATURES, 0, &eax, &ebx, &ecx, &edx);
+
+    uint32_t msr;
+    if (eax & KVM_FEATURE_CLOCKSOURCE2)
+    {
+        msr = MSR_KVM_SYSTEM_TIME_NEW;
+    }
+    else if (eax & KVM_FEATURE_CLOCKSOURCE)
+    {
+        msr = MSR_KVM_SYSTEM_TIME;
+    }
+    else
+    {
+        return false;
+    }
+
+    // Paging is off, so the linear address of the structure is also its physical address
+    wrmsr(msr, (uint32_t) &kvmclock | KVM_SYSTEM_TIME_ENABLE);
+    kvmclock_active = true;
+    return true;
+}
+
+bool kvmclock_enabled(void)
+{
+    return kvmclock_active;
+}
+
+uint64_t kvmclock_read_ns(void)
+{
+    uint32_t version;
+    uint64_t ns;
+
+    // The hypervisor makes the version odd while it updates the structure
+    do
+    {
+        version = kvmclock.version;
+        __asm__ volatile ("" ::: "memory");
+
+        uint64_t delta = rdtsc() - kvmclock.tsc_timestamp;
+        int8_t shift = kvmclock.tsc_shift;
+        if (shift < 0)
+        {
+            delta >>= -shift;
+        }
+        else
+        {
+            delta <<= shift;
+        }
+
+        // (delta * mul) >> 32 without overflowing 64 bits
+        uint32_t mul = kvmclock.tsc_to_system_mul;
+        uint64_t lo = (delta & 0xFFFFFFFF) * mul;
+        uint64_t hi = (delta >> 32) * mul;
+        ns = kvmclock.system_time + hi + (lo >> 32);
+
+        __asm__ volatile ("" ::: "memory");
+    } while ((version & 1) || version != kvmclock.version);
+
+    return ns;
+}
+
+bool arch_pv_clock(uint64_t *ns)
+{
+    if (!kvmclock_active)
+    {
+        return false;
+    }
+
+    *ns = kvmclock_read_ns();
+    return true;
+}
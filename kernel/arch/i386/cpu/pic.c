@@ -0,0 +1,91 @@
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <cpu/pic.h>
+#include <cpu/ports.h>
+
+#define PIC1_COMMAND 0x20
+#define PIC1_DATA 0x21
+#define PIC2_COMMAND 0xA0
+#define PIC2_DATA 0xA1
+
+#define PIC_EOI 0x20
+#define PIC_READ_ISR 0x0B
+
+#define ICW1_ICW4 0x01
+#define ICW1_INIT 0x10
+#define ICW4_8086 0x01
+
+#define PIC_CASCADE_IRQ 2
+
+// Move the PICs off the CPU exception vectors and mask every line but the cascade
+void pic_init(uint8_t offset)
+{
+    outb(PIC1_COMMAND, ICW1_INIT | ICW1_ICW4);
+    io_wait();
+    outb(PIC2_COMMAND, ICW1_INIT | ICW1_ICW4);
+    io_wait();
+    outb(PIC1_DATA, offset);
+    io_wait();
+    outb(PIC2_DATA, offset + 8);
+    io_wait();
+    outb(PIC1_DATA, 1 << PIC_CASCADE_IRQ);
+    io_wait();
+    outb(PIC2_DATA, PIC_CASCADE_IRQ);
+    io_wait();
+    outb(PIC1_DATA, ICW4_8086);
+    io_wait();
+    outb(PIC2_DATA, ICW4_8086);
+    io_wait();
+
+    outb(PIC1_DATA, ~(1 << PIC_CASCADE_IRQ) & 0xFF);
+    outb(PIC2_DATA, 0xFF);
+}
+
+void pic_eoi(uint8_t irq)
+{
+    if (irq >= 8)
+    {
+        outb(PIC2_COMMAND, PIC_EOI);
+    }
+    outb(PIC1_COMMAND, PIC_EOI);
+}
+
+void pic_mask(uint8_t irq)
+{
+    uint16_t port = irq < 8 ? PIC1_DATA : PIC2_DATA;
+    outb(port, inb(port) | (1 << (irq % 8)));
+}
+
+void pic_unmask(uint8_t irq)
+{
+    uint16_t port = irq < 8 ? PIC1_DATA : PIC2_DATA;
+    outb(port, inb(port) & ~(1 << (irq % 8)));
+}
+
+static uint16_t pic_isr(void)
+{
+    outb(PIC1_COMMAND, PIC_READ_ISR);
+    outb(PIC2_COMMAND, PIC_READ_ISR);
+    return (inb(PIC2_COMMAND) << 8) | inb(PIC1_COMMAND);
+}
+
+// A line dropping before the CPU acknowledges it shows up as IRQ 7 or 15 without its
+// in-service bit set. Those must not be EOIed, except that the master still saw the cascade.
+bool pic_spurious(uint8_t irq)
+{
+    if (irq != 7 && irq != 15)
+    {
+        return false;
+    }
+    if (pic_isr() & (1 << irq))
+    {
+        return false;
+    }
+
+    if (irq == 15)
+    {
+        outb(PIC1_COMMAND, PIC_EOI);
+    }
+    return true;
+}
@@ -0,0 +1,63 @@
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <cpu.h>
+#include <cpu/cpuid.h>
+
+#define RANDOM_RETRIES 10
+
+static bool has_rdrand(void)
+{
+    uint32_t eax, ebx, ecx, edx;
+    cpuid(CPUID_FEATURES, 0, &eax, &ebx, &ecx, &edx);
+    return ecx & CPUID_FEAT_ECX_RDRAND;
+}
+
+static bool has_rdseed(void)
+{
+    uint32_t eax, ebx, ecx, edx;
+    if (cpuid_max_leaf() < CPUID_EXT_FEATURES)
+    {
+        return false;
+    }
+    cpuid(CPUID_EXT_FEATURES, 0, &eax, &ebx, &ecx, &edx);
+    return ebx & CPUID_EXT_FEAT_EBX_RDSEED;
+}
+
+bool arch_random_seed(uint32_t *value)
+{
+    if (!has_rdseed())
+    {
+        return false;
+    }
+
+    for (int i = 0; i < RANDOM_RETRIES; ++i)
+    {
+        uint8_t ok;
+        __asm__ volatile ("rdseed %0; setc %1" : "=r"(*value), "=qm"(ok));
+        if (ok)
+        {
+            return true;
+        }
+    }
+    return false;
+}
+
+bool arch_random(uint32_t *value)
+{
+    if (!has_rdrand())
+    {
+        return false;
+    }
+
+    for (int i = 0; i < RANDOM_RETRIES; ++i)
+    {
+        uint8_t ok;
+        __asm__ volatile ("rdrand %0; setc %1" : "=r"(*value), "=qm"(ok));
+        if (ok)
+        {
+            return true;
+        }
+    }
+    return false;
+}
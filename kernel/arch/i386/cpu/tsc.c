@@ -0,0 +1,71 @@
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <cpu.h>
+#include <cpu/cpuid.h>
+#include <cpu/tsc.h>
+#include <drivers/timer/pit.h>
+
+#define CALIBRATE_MS 10
+#define CALIBRATE_RUNS 3
+
+static uint64_t tsc_hz;
+static bool tsc_is_invariant;
+
+// Time a PIT channel 2 one-shot countdown of CALIBRATE_MS in TSC cycles
+static uint64_t pit_calibrate_once(void)
+{
+    pit_countdown_start(PIT_FREQUENCY * CALIBRATE_MS / 1000);
+
+    uint64_t start = rdtsc();
+    while (!pit_countdown_done())
+    {
+    }
+    uint64_t end = rdtsc();
+
+    pit_countdown_stop();
+    return end - start;
+}
+
+void tsc_init(void)
+{
+    uint32_t eax, ebx, ecx, edx;
+    if (cpuid_max_ext_leaf() >= CPUID_POWER_MGMT)
+    {
+        cpuid(CPUID_POWER_MGMT, 0, &eax, &ebx, &ecx, &edx);
+        tsc_is_invariant = edx & CPUID_PM_EDX_INVARIANT_TSC;
+    }
+
+    // Take the shortest run, the longer ones were disturbed by something like an SMI
+    uint64_t best = UINT64_MAX;
+    for (int i = 0; i < CALIBRATE_RUNS; ++i)
+    {
+        uint64_t cycles = pit_calibrate_once();
+        if (cycles < best)
+        {
+            best = cycles;
+        }
+    }
+
+    tsc_hz = best * 1000 / CALIBRATE_MS;
+}
+
+bool tsc_invariant(void)
+{
+    return tsc_is_invariant;
+}
+
+uint64_t tsc_frequency(void)
+{
+    return tsc_hz;
+}
+
+uint64_t arch_cycles(void)
+{
+    return rdtsc();
+}
+
+uint64_t arch_cycles_frequency(void)
+{
+    return tsc_hz;
+}
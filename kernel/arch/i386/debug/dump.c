@@ -0,0 +1,103 @@
+#include <stddef.h>
+#include <stdint.h>
+
+#include <cpu.h>
+#include <cpu/interrupts.h>
+#include <libk/io.h>
+
+#define BACKTRACE_MAX 16
+
+#define READ_CR(n, value) __asm__ volatile ("mov %%cr" #n ", %0" : "=r"(value))
+
+uintptr_t arch_frame_stack(const void *frame)
+{
+    const interrupt_registers_t *regs = frame;
+    if (!regs)
+    {
+        return (uintptr_t) __builtin_frame_address(0);
+    }
+    // The kernel only runs in ring 0, so no esp/ss were pushed and the old stack starts where they would be
+    return (uintptr_t) &regs->esp;
+}
+
+void arch_frame_registers(const void *frame, arch_register_callback_t callback)
+{
+    const interrupt_registers_t *regs = frame;
+    if (!regs)
+    {
+        return;
+    }
+
+    callback("eax", regs->eax);
+    callback("ebx", regs->ebx);
+    callback("ecx", regs->ecx);
+    callback("edx", regs->edx);
+    callback("esi", regs->esi);
+    callback("edi", regs->edi);
+    callback("ebp", regs->ebp);
+    callback("esp", arch_frame_stack(frame));
+    callback("eip", regs->eip);
+    callback("eflags", regs->eflags);
+    callback("cs", regs->cs);
+    callback("ds", regs->ds);
+    callback("int_no", regs->int_no);
+    callback("err_code", regs->err_code);
+}
+
+void arch_dump_registers(const void *frame)
+{
+    const interrupt_registers_t *regs = frame;
+    if (regs)
+    {
+        uint32_t esp = arch_frame_stack(frame);
+
+        kprintf("Interrupt %u, error code %08x\n", regs->int_no, regs->err_code);
+        kprintf("EAX=%08x EBX=%08x ECX=%08x EDX=%08x\n", regs->eax, regs->ebx, regs->ecx, regs->edx);
+        kprintf("ESI=%08x EDI=%08x EBP=%08x ESP=%08x\n", regs->esi, regs->edi, regs->ebp, esp);
+        kprintf("EIP=%08x EFL=%08x CS=%04x DS=%04x\n", regs->eip, regs->eflags, regs->cs, regs->ds);
+    }
+
+    uint32_t cr0, cr2, cr3, cr4;
+    READ_CR(0, cr0);
+    READ_CR(2, cr2);
+    READ_CR(3, cr3);
+    READ_CR(4, cr4);
+    kprintf("CR0=%08x CR2=%08x CR3=%08x CR4=%08x\n", cr0, cr2, cr3, cr4);
+}
+
+void arch_backtrace(const void *frame)
+{
+    const interrupt_registers_t *regs = frame;
+    const uint32_t *fp;
+    size_t depth = 0;
+
+    if (regs)
+    {
+        kprintf("  #0 %08x\n", regs->eip);
+        fp = (const uint32_t*) regs->ebp;
+        ++depth;
+    }
+    else
+    {
+        fp = __builtin_frame_address(0);
+    }
+
+    // Each frame holds the caller's ebp followed by the return address.
+    // Frames only ever move up the stack, anything else means the chain is corrupt.
+    while (fp && ((uintptr_t) fp & 3) == 0 && depth < BACKTRACE_MAX)
+    {
+        uint32_t ret = fp[1];
+        if (ret == 0)
+        {
+            break;
+        }
+        kprintf("  #%u %08x\n", (unsigned int) depth++, ret);
+
+        const uint32_t *next = (const uint32_t*) fp[0];
+        if (next <= fp)
+        {
+            break;
+        }
+        fp = next;
+    }
+}
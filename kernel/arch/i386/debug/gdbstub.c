@@ -0,0 +1,383 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <cpu/interrupts.h>
+#include <debug/gdbstub.h>
+#include <drivers/serial/uart.h>
+#include <libk/string.h>
+
+#define GDB_BUFFER_SIZE 512
+// Stopped with SIGTRAP
+#define GDB_STOP_REPLY "S05"
+
+#define EFLAGS_TRAP (1 << 8)
+
+// Register numbering used by GDB for i386 targets
+enum gdb_register
+{
+    GDB_EAX,
+    GDB_ECX,
+    GDB_EDX,
+    GDB_EBX,
+    GDB_ESP,
+    GDB_EBP,
+    GDB_ESI,
+    GDB_EDI,
+    GDB_EIP,
+    GDB_EFLAGS,
+    GDB_CS,
+    GDB_SS,
+    GDB_DS,
+    GDB_ES,
+    GDB_FS,
+    GDB_GS,
+    GDB_NUM_REGISTERS,
+};
+
+static const char hex_chars[] = "0123456789abcdef";
+
+static bool gdb_enabled;
+static bool gdb_attached;
+static char in_buf[GDB_BUFFER_SIZE];
+static char out_buf[GDB_BUFFER_SIZE];
+
+static int hex_value(char c)
+{
+    if (c >= '0' && c <= '9')
+    {
+        return c - '0';
+    }
+    if (c >= 'a' && c <= 'f')
+    {
+        return c - 'a' + 10;
+    }
+    if (c >= 'A' && c <= 'F')
+    {
+        return c - 'A' + 10;
+    }
+    return -1;
+}
+
+// Parse a hex number, advancing the string past it
+static uint32_t parse_hex(const char **str)
+{
+    uint32_t value = 0;
+    int digit;
+    while ((digit = hex_value(**str)) >= 0)
+    {
+        value = (value << 4) | digit;
+        ++*str;
+    }
+    return value;
+}
+
+static char *mem_to_hex(const uint8_t *mem, char *buf, size_t len)
+{
+    for (size_t i = 0; i < len; ++i)
+    {
+        *buf++ = hex_chars[mem[i] >> 4];
+        *buf++ = hex_chars[mem[i] & 0xF];
+    }
+    *buf = '\0';
+    return buf;
+}
+
+static const char *hex_to_mem(const char *buf, uint8_t *mem, size_t len)
+{
+    for (size_t i = 0; i < len; ++i)
+    {
+        int hi = hex_value(buf[0]);
+        int lo = hex_value(buf[1]);
+        if (hi < 0 || lo < 0)
+        {
+            return NULL;
+        }
+        mem[i] = (hi << 4) | lo;
+        buf += 2;
+    }
+    return buf;
+}
+
+static void gdb_receive_packet(char *buf, size_t max)
+{
+    while (true)
+    {
+        while (serial_getc(GDBSTUB_SERIAL_PORT) != '$')
+        {
+        }
+
+        size_t len = 0;
+        uint8_t checksum = 0;
+        char c;
+        while ((c = serial_getc(GDBSTUB_SERIAL_PORT)) != '#')
+        {
+            if (c == '$')
+            {
+                len = 0;
+                checksum = 0;
+                continue;
+            }
+            if (len < max - 1)
+            {
+                buf[len++] = c;
+            }
+            checksum += c;
+        }
+        buf[len] = '\0';
+
+        int hi = hex_value(serial_getc(GDBSTUB_SERIAL_PORT));
+        int lo = hex_value(serial_getc(GDBSTUB_SERIAL_PORT));
+        if (hi >= 0 && lo >= 0 && ((hi << 4) | lo) == checksum)
+        {
+            serial_putc(GDBSTUB_SERIAL_PORT, '+');
+            gdb_attached = true;
+            return;
+        }
+
+        serial_putc(GDBSTUB_SERIAL_PORT, '-');
+    }
+}
+
+static void gdb_send_packet(const char *data)
+{
+    size_t len = strlen(data);
+    uint8_t checksum = 0;
+    for (size_t i = 0; i < len; ++i)
+    {
+        checksum += data[i];
+    }
+
+    do
+    {
+        serial_putc(GDBSTUB_SERIAL_PORT, '$');
+        serial_write(GDBSTUB_SERIAL_PORT, data, len);
+        serial_putc(GDBSTUB_SERIAL_PORT, '#');
+        serial_putc(GDBSTUB_SERIAL_PORT, hex_chars[checksum >> 4]);
+        serial_putc(GDBSTUB_SERIAL_PORT, hex_chars[checksum & 0xF]);
+    } while (serial_getc(GDBSTUB_SERIAL_PORT) != '+');
+}
+
+static void read_registers(interrupt_registers_t *regs, uint32_t *gdb_regs)
+{
+    uint16_t ss;
+    __asm__ volatile ("mov %%ss, %0" : "=r"(ss));
+
+    gdb_regs[GDB_EAX] = regs->eax;
+    gdb_regs[GDB_ECX] = regs->ecx;
+    gdb_regs[GDB_EDX] = regs->edx;
+    gdb_regs[GDB_EBX] = regs->ebx;
+    // Exceptions taken in ring 0 don't push esp/ss, the interrupted stack starts where they would be
+    gdb_regs[GDB_ESP] = (uint32_t) &regs->esp;
+    gdb_regs[GDB_EBP] = regs->ebp;
+    gdb_regs[GDB_ESI] = regs->esi;
+    gdb_regs[GDB_EDI] = regs->edi;
+    gdb_regs[GDB_EIP] = regs->eip;
+    gdb_regs[GDB_EFLAGS] = regs->eflags;
+    gdb_regs[GDB_CS] = regs->cs;
+    gdb_regs[GDB_SS] = ss;
+    gdb_regs[GDB_DS] = regs->ds;
+    gdb_regs[GDB_ES] = regs->ds;
+    gdb_regs[GDB_FS] = regs->ds;
+    gdb_regs[GDB_GS] = regs->ds;
+}
+
+// Only general purpose registers, eip and eflags can be changed, the rest are fixed by the kernel
+static void write_register(interrupt_registers_t *regs, int reg, uint32_t value)
+{
+    switch (reg)
+    {
+        case GDB_EAX: regs->eax = value; break;
+        case GDB_ECX: regs->ecx = value; break;
+        case GDB_EDX: regs->edx = value; break;
+        case GDB_EBX: regs->ebx = value; break;
+        case GDB_EBP: regs->ebp = value; break;
+        case GDB_ESI: regs->esi = value; break;
+        case GDB_EDI: regs->edi = value; break;
+        case GDB_EIP: regs->eip = value; break;
+        case GDB_EFLAGS: regs->eflags = value; break;
+        default: break;
+    }
+}
+
+// Most bytes a memory read reply can hold as hex. Compared without multiplying, a length from
+// the wire could otherwise wrap around and slip past the clamp.
+static uint32_t read_memory_len(uint32_t len)
+{
+    if (len > (GDB_BUFFER_SIZE - 1) / 2)
+    {
+        return (GDB_BUFFER_SIZE - 1) / 2;
+    }
+    return len;
+}
+
+static void handle_read_memory(const char *args)
+{
+    uint32_t addr = parse_hex(&args);
+    if (*args++ != ',')
+    {
+        gdb_send_packet("E01");
+        return;
+    }
+    uint32_t len = read_memory_len(parse_hex(&args));
+
+    mem_to_hex((const uint8_t*) addr, out_buf, len);
+    gdb_send_packet(out_buf);
+}
+
+static void handle_write_memory(const char *args)
+{
+    uint32_t addr = parse_hex(&args);
+    if (*args++ != ',')
+    {
+        gdb_send_packet("E01");
+        return;
+    }
+    uint32_t len = parse_hex(&args);
+    if (*args++ != ':')
+    {
+        gdb_send_packet("E01");
+        return;
+    }
+
+    if (hex_to_mem(args, (uint8_t*) addr, len) == NULL)
+    {
+        gdb_send_packet("E02");
+        return;
+    }
+    gdb_send_packet("OK");
+}
+
+static void handle_write_registers(interrupt_registers_t *regs, const char *args)
+{
+    uint32_t gdb_regs[GDB_NUM_REGISTERS];
+    if (hex_to_mem(args, (uint8_t*) gdb_regs, sizeof(gdb_regs)) == NULL)
+    {
+        gdb_send_packet("E01");
+        return;
+    }
+
+    for (int i = 0; i < GDB_NUM_REGISTERS; ++i)
+    {
+        write_register(regs, i, gdb_regs[i]);
+    }
+    gdb_send_packet("OK");
+}
+
+static void handle_write_register(interrupt_registers_t *regs, const char *args)
+{
+    uint32_t reg = parse_hex(&args);
+    uint32_t value;
+    if (*args++ != '=' || reg >= GDB_NUM_REGISTERS || hex_to_mem(args, (uint8_t*) &value, sizeof(value)) == NULL)
+    {
+        gdb_send_packet("E01");
+        return;
+    }
+
+    write_register(regs, reg, value);
+    gdb_send_packet("OK");
+}
+
+void gdbstub_init(void)
+{
+    gdb_enabled = serial_present(GDBSTUB_SERIAL_PORT);
+    if (gdb_enabled)
+    {
+        interrupt_register_handler(INT_DEBUG, gdbstub_handle_exception, NULL);
+        interrupt_register_handler(INT_BREAKPOINT, gdbstub_handle_exception, NULL);
+    }
+}
+
+bool gdbstub_enabled(void)
+{
+    return gdb_enabled;
+}
+
+void gdbstub_handle_exception(interrupt_registers_t *regs, void *data)
+{
+    (void) data;
+    uint32_t gdb_regs[GDB_NUM_REGISTERS];
+
+    // GDB is waiting on a stop reply after a continue or step
+    if (gdb_attached)
+    {
+        gdb_send_packet(GDB_STOP_REPLY);
+    }
+
+    while (true)
+    {
+        gdb_receive_packet(in_buf, GDB_BUFFER_SIZE);
+        const char *args = in_buf + 1;
+
+        switch (in_buf[0])
+        {
+            case '?':
+                gdb_send_packet(GDB_STOP_REPLY);
+                break;
+            case 'g':
+                read_registers(regs, gdb_regs);
+                mem_to_hex((const uint8_t*) gdb_regs, out_buf, sizeof(gdb_regs));
+                gdb_send_packet(out_buf);
+                break;
+            case 'G':
+                handle_write_registers(regs, args);
+                break;
+            case 'P':
+                handle_write_register(regs, args);
+                break;
+            case 'm':
+                handle_read_memory(args);
+                break;
+            case 'M':
+                handle_write_memory(args);
+                break;
+            case 'H':
+                gdb_send_packet("OK");
+                break;
+            case 'c':
+            case 's':
+                if (*args)
+                {
+                    regs->eip = parse_hex(&args);
+                }
+                if (in_buf[0] == 's')
+                {
+                    regs->eflags |= EFLAGS_TRAP;
+                }
+                else
+                {
+                    regs->eflags &= ~EFLAGS_TRAP;
+                }
+                return;
+            case 'D':
+                gdb_send_packet("OK");
+                gdb_attached = false;
+                regs->eflags &= ~EFLAGS_TRAP;
+                return;
+            case 'k':
+                gdb_attached = false;
+                regs->eflags &= ~EFLAGS_TRAP;
+                return;
+            default:
+                // An empty reply tells GDB the packet isn't supported
+                gdb_send_packet("");
+                break;
+        }
+    }
+}
+
+#ifdef KERNEL_TEST
+#include <test.h>
+
+TEST_CASE(gdbstub_clamps_memory_reads)
+{
+    TEST_ASSERT(read_memory_len(0) == 0);
+    TEST_ASSERT(read_memory_len(16) == 16);
+    TEST_ASSERT(read_memory_len((GDB_BUFFER_SIZE - 1) / 2) == (GDB_BUFFER_SIZE - 1) / 2);
+    TEST_ASSERT(read_memory_len(GDB_BUFFER_SIZE / 2) == (GDB_BUFFER_SIZE - 1) / 2);
+    // len * 2 wraps to 0 and 2 for these
+    TEST_ASSERT(read_memory_len(0x80000000) == (GDB_BUFFER_SIZE - 1) / 2);
+    TEST_ASSERT(read_memory_len(0x80000001) == (GDB_BUFFER_SIZE - 1) / 2);
+    TEST_ASSERT(read_memory_len(UINT32_MAX) == (GDB_BUFFER_SIZE - 1) / 2);
+}
+#endif
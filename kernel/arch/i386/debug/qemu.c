@@ -0,0 +1,12 @@
+#include <stdint.h>
+
+#include <cpu.h>
+#include <cpu/ports.h>
+
+// Where `-device isa-debug-exit,iobase=0xf4,iosize=0x04` is mapped
+#define QEMU_EXIT_PORT 0xF4
+
+void arch_qemu_exit(uint32_t code)
+{
+    outl(QEMU_EXIT_PORT, code);
+}
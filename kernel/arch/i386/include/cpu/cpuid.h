@@ -0,0 +1,47 @@
+#ifndef ARCH_I386_CPUID_H
+#define ARCH_I386_CPUID_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define CPUID_FEATURES 0x01
+#define CPUID_EXT_FEATURES 0x07
+#define CPUID_EXT_MAX_LEAF 0x80000000
+#define CPUID_POWER_MGMT 0x80000007
+#define CPUID_HYPERVISOR_BASE 0x40000000
+
+#define CPUID_FEAT_ECX_RDRAND (1 << 30)
+#define CPUID_FEAT_ECX_HYPERVISOR (1 << 31)
+#define CPUID_FEAT_EDX_TSC (1 << 4)
+#define CPUID_EXT_FEAT_EBX_RDSEED (1 << 18)
+#define CPUID_PM_EDX_INVARIANT_TSC (1 << 8)
+
+static inline void cpuid(uint32_t leaf, uint32_t subleaf, uint32_t *eax, uint32_t *ebx, uint32_t *ecx, uint32_t *edx)
+{
+    __asm__ volatile ("cpuid"
+        : "=a"(*eax), "=b"(*ebx), "=c"(*ecx), "=d"(*edx)
+        : "a"(leaf), "c"(subleaf));
+}
+
+static inline uint32_t cpuid_max_leaf(void)
+{
+    uint32_t eax, ebx, ecx, edx;
+    cpuid(0, 0, &eax, &ebx, &ecx, &edx);
+    return eax;
+}
+
+static inline uint32_t cpuid_max_ext_leaf(void)
+{
+    uint32_t eax, ebx, ecx, edx;
+    cpuid(CPUID_EXT_MAX_LEAF, 0, &eax, &ebx, &ecx, &edx);
+    return eax;
+}
+
+static inline uint64_t rdtsc(void)
+{
+    uint32_t lo, hi;
+    __asm__ volatile ("rdtsc" : "=a"(lo), "=d"(hi));
+    return ((uint64_t) hi << 32) | lo;
+}
+
+#endif
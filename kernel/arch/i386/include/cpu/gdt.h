@@ -15,7 +15,8 @@
 #define GDT_FLAGS_SIZE 1 << 6
 #define GDT_FLAGS_LONG 1 << 5
 
-#define KERNEL_DATA_SEL 0x08
+#define KERNEL_CODE_SEL 0x08
+#define KERNEL_DATA_SEL 0x10
 
 void gdt_init(void);
 
@@ -1,6 +1,8 @@
 #ifndef ARCH_I386_IDT_H
 #define ARCH_I386_IDT_H
 
+#include <stdint.h>
+
 #define IDT_ENTRIES 256
 
 #define IDT_TASK_GATE 0x05
@@ -8,7 +10,7 @@
 #define IDT_16_BIT_TRAP 0x07
 #define IDT_32_BIT_INT 0x0E
 #define IDT_32_BIT_TRAP 0x0F
-#define IDT_PRESENT 1 << 7
+#define IDT_PRESENT (1 << 7)
 
 void idt_init(void);
 
@@ -27,7 +29,7 @@ typedef struct idt_ptr_t
     uint32_t offset;
 } __attribute__((packed)) idt_ptr_t;
 
-// Defined in interrupt.asm
+// Defined in interrupts-asm.asm
 extern void flush_idt(idt_ptr_t*);
 extern void isr_0(void);
 extern void isr_1(void);
@@ -61,6 +63,22 @@ extern void isr_28(void);
 extern void isr_29(void);
 extern void isr_30(void);
 extern void isr_31(void);
+extern void irq_0(void);
+extern void irq_1(void);
+extern void irq_2(void);
+extern void irq_3(void);
+extern void irq_4(void);
+extern void irq_5(void);
+extern void irq_6(void);
+extern void irq_7(void);
+extern void irq_8(void);
+extern void irq_9(void);
+extern void irq_10(void);
+extern void irq_11(void);
+extern void irq_12(void);
+extern void irq_13(void);
+extern void irq_14(void);
+extern void irq_15(void);
 
 
 #endif
\ No newline at end of file
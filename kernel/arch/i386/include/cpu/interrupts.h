@@ -0,0 +1,55 @@
+#ifndef ARCH_I386_INTERRUPTS_H
+#define ARCH_I386_INTERRUPTS_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define INT_DEBUG 0x01
+#define INT_BREAKPOINT 0x03
+
+#define IRQ_BASE 0x20
+#define IRQ_COUNT 16
+#define IRQ_VECTOR(irq) (IRQ_BASE + (irq))
+
+#define IRQ_TIMER 0
+
+typedef struct interrupt_registers_t
+{
+    uint32_t ds;
+    uint32_t edi, esi, ebp, useless, ebx, edx, ecx, eax;
+    uint32_t int_no, err_code;
+    uint32_t eip, cs, eflags, esp, ss;
+} interrupt_registers_t;
+
+// data is whatever the driver passed at registration
+typedef void (*interrupt_handler_t)(interrupt_registers_t *regs, void *data);
+
+// Handled interrupts and the time spent in their handler
+typedef struct interrupt_stats_t
+{
+    uint64_t count;
+    uint64_t total_cycles;
+    uint64_t max_cycles;
+} interrupt_stats_t;
+
+void interrupt_register_handler(uint8_t vector, interrupt_handler_t handler, void *data);
+void interrupt_unregister_handler(uint8_t vector);
+void interrupt_get_stats(uint8_t vector, interrupt_stats_t *stats);
+
+// Attach a handler to a legacy IRQ line and unmask it, EOI is sent by the dispatcher.
+// Fails if the line is owned by someone else; the owner may register again.
+bool irq_register_handler(uint8_t irq, interrupt_handler_t handler, void *data, const char *owner);
+// Mask the line and release it for another driver
+void irq_free(uint8_t irq);
+void irq_mask(uint8_t irq);
+void irq_unmask(uint8_t irq);
+// Name of the driver holding a line, NULL if free
+const char *irq_owner(uint8_t irq);
+
+// Interrupts dropped as spurious, and ones that arrived with no handler attached
+uint32_t irq_spurious_count(uint8_t irq);
+uint32_t irq_unhandled_count(uint8_t irq);
+
+void interrupts_init(void);
+
+#endif
@@ -0,0 +1,12 @@
+#ifndef ARCH_I386_KVM_H
+#define ARCH_I386_KVM_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+bool kvm_detect(void);
+bool kvmclock_init(void);
+bool kvmclock_enabled(void);
+uint64_t kvmclock_read_ns(void);
+
+#endif
@@ -0,0 +1,16 @@
+#ifndef ARCH_I386_PIC_H
+#define ARCH_I386_PIC_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define PIC_IRQS 16
+
+void pic_init(uint8_t offset);
+void pic_eoi(uint8_t irq);
+void pic_mask(uint8_t irq);
+void pic_unmask(uint8_t irq);
+// Check an interrupt on a line against the in-service register, handling the EOI if it was spurious
+bool pic_spurious(uint8_t irq);
+
+#endif
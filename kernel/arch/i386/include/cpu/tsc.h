@@ -0,0 +1,11 @@
+#ifndef ARCH_I386_TSC_H
+#define ARCH_I386_TSC_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+void tsc_init(void);
+bool tsc_invariant(void);
+uint64_t tsc_frequency(void);
+
+#endif
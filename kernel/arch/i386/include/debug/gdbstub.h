@@ -0,0 +1,21 @@
+#ifndef ARCH_I386_GDBSTUB_H
+#define ARCH_I386_GDBSTUB_H
+
+#include <stdbool.h>
+
+#include <cpu/interrupts.h>
+
+// The stub speaks the remote protocol on COM1, nothing else may write to it
+#define GDBSTUB_SERIAL_PORT 0
+
+void gdbstub_init(void);
+bool gdbstub_enabled(void);
+void gdbstub_handle_exception(interrupt_registers_t *regs, void *data);
+
+// Trap into the debugger, used to hand control to GDB at boot
+static inline void gdbstub_breakpoint(void)
+{
+    __asm__ volatile ("int3");
+}
+
+#endif
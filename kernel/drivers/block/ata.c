@@ -0,0 +1,349 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <cpu/ports.h>
+#include <drivers/block/ata.h>
+#include <drivers/block/block.h>
+#include <log.h>
+#include <time/time.h>
+
+#define ATA_PRIMARY_IO 0x1F0
+#define ATA_PRIMARY_CTRL 0x3F6
+#define ATA_SECONDARY_IO 0x170
+#define ATA_SECONDARY_CTRL 0x376
+
+// Register offsets from the IO base
+#define ATA_REG_DATA 0
+#define ATA_REG_ERROR 1
+#define ATA_REG_SECCOUNT 2
+#define ATA_REG_LBA_LO 3
+#define ATA_REG_LBA_MID 4
+#define ATA_REG_LBA_HI 5
+#define ATA_REG_DRIVE 6
+#define ATA_REG_STATUS 7
+#define ATA_REG_COMMAND 7
+
+#define ATA_STATUS_ERR (1 << 0)
+#define ATA_STATUS_DRQ (1 << 3)
+#define ATA_STATUS_DF (1 << 5)
+#define ATA_STATUS_BSY (1 << 7)
+
+#define ATA_CTRL_NIEN (1 << 1)
+
+#define ATA_CMD_READ_PIO 0x20
+#define ATA_CMD_READ_PIO_EXT 0x24
+#define ATA_CMD_WRITE_PIO 0x30
+#define ATA_CMD_WRITE_PIO_EXT 0x34
+#define ATA_CMD_CACHE_FLUSH 0xE7
+#define ATA_CMD_CACHE_FLUSH_EXT 0xEA
+#define ATA_CMD_IDENTIFY 0xEC
+
+#define ATA_DRIVE_CHS 0xA0
+#define ATA_DRIVE_LBA 0xE0
+#define ATA_DRIVE_LBA48 0x40
+#define ATA_DRIVE_SLAVE (1 << 4)
+
+// Offsets into the IDENTIFY data, in 16-bit words
+#define ATA_IDENT_MODEL 27
+#define ATA_IDENT_MODEL_WORDS 20
+#define ATA_IDENT_COMMAND_SETS 83
+#define ATA_IDENT_LBA28_SECTORS 60
+#define ATA_IDENT_LBA48_SECTORS 100
+#define ATA_COMMAND_SET_LBA48 (1 << 10)
+
+#define ATA_SECTOR_SIZE 512
+#define ATA_MAX_SECTORS 128
+#define ATA_LBA28_LIMIT 0x0FFFFFFF
+#define ATA_MAX_DRIVES 4
+
+// Give a drive spinning up plenty of time, but don't wait forever on a wedged one
+#define ATA_TIMEOUT_MS 5000
+#define ATA_POLL_US 10
+#define ATA_TIMEOUT_POLLS (ATA_TIMEOUT_MS * 1000 / ATA_POLL_US)
+
+typedef struct ata_drive_t
+{
+    uint16_t io;
+    uint16_t ctrl;
+    bool slave;
+    bool lba48;
+    char model[ATA_IDENT_MODEL_WORDS * 2 + 1];
+    block_device_t dev;
+} ata_drive_t;
+
+static ata_drive_t drives[ATA_MAX_DRIVES];
+static const char *drive_names[ATA_MAX_DRIVES] = { "ata0", "ata1", "ata2", "ata3" };
+
+// Reading the alternate status register takes ~100ns, four reads give the drive its 400ns
+static void ata_delay(ata_drive_t *drive)
+{
+    for (int i = 0; i < 4; ++i)
+    {
+        inb(drive->ctrl);
+    }
+}
+
+static void ata_timeout(ata_drive_t *drive)
+{
+    klog(LOG_WARN, "ata", "Drive at %x%s timed out", drive->io, drive->slave ? " (slave)" : "");
+}
+
+// Returns the status once BSY clears, false if the drive stays busy too long
+static bool ata_wait_idle(ata_drive_t *drive, uint8_t *status)
+{
+    for (int i = 0; i < ATA_TIMEOUT_POLLS; ++i)
+    {
+        *status = inb(drive->io + ATA_REG_STATUS);
+        if (!(*status & ATA_STATUS_BSY))
+        {
+            return true;
+        }
+        udelay(ATA_POLL_US);
+    }
+    ata_timeout(drive);
+    return false;
+}
+
+static bool ata_wait_ready(ata_drive_t *drive)
+{
+    uint8_t status;
+    return ata_wait_idle(drive, &status) && !(status & (ATA_STATUS_ERR | ATA_STATUS_DF));
+}
+
+static bool ata_wait_drq(ata_drive_t *drive)
+{
+    ata_delay(drive);
+    for (int i = 0; i < ATA_TIMEOUT_POLLS; ++i)
+    {
+        uint8_t status = inb(drive->io + ATA_REG_STATUS);
+        if (status & (ATA_STATUS_ERR | ATA_STATUS_DF))
+        {
+            return false;
+        }
+        if (!(status & ATA_STATUS_BSY) && (status & ATA_STATUS_DRQ))
+        {
+            return true;
+        }
+        udelay(ATA_POLL_US);
+    }
+    ata_timeout(drive);
+    return false;
+}
+
+static void ata_select(ata_drive_t *drive, uint8_t mode)
+{
+    outb(drive->io + ATA_REG_DRIVE, mode | (drive->slave ? ATA_DRIVE_SLAVE : 0));
+    ata_delay(drive);
+}
+
+static void ata_issue(ata_drive_t *drive, uint64_t lba, size_t count, bool write)
+{
+    bool lba48 = drive->lba48 && lba + count > ATA_LBA28_LIMIT;
+    if (lba48)
+    {
+        ata_select(drive, ATA_DRIVE_LBA48);
+        outb(drive->io + ATA_REG_SECCOUNT, (count >> 8) & 0xFF);
+        outb(drive->io + ATA_REG_LBA_LO, (lba >> 24) & 0xFF);
+        outb(drive->io + ATA_REG_LBA_MID, (lba >> 32) & 0xFF);
+        outb(drive->io + ATA_REG_LBA_HI, (lba >> 40) & 0xFF);
+    }
+    else
+    {
+        ata_select(drive, ATA_DRIVE_LBA | ((lba >> 24) & 0x0F));
+    }
+
+    outb(drive->io + ATA_REG_SECCOUNT, count & 0xFF);
+    outb(drive->io + ATA_REG_LBA_LO, lba & 0xFF);
+    outb(drive->io + ATA_REG_LBA_MID, (lba >> 8) & 0xFF);
+    outb(drive->io + ATA_REG_LBA_HI, (lba >> 16) & 0xFF);
+
+    if (write)
+    {
+        outb(drive->io + ATA_REG_COMMAND, lba48 ? ATA_CMD_WRITE_PIO_EXT : ATA_CMD_WRITE_PIO);
+    }
+    else
+    {
+        outb(drive->io + ATA_REG_COMMAND, lba48 ? ATA_CMD_READ_PIO_EXT : ATA_CMD_READ_PIO);
+    }
+}
+
+static bool ata_read(block_device_t *dev, uint64_t lba, size_t count, void *buf)
+{
+    ata_drive_t *drive = (ata_drive_t*) dev->data;
+    uint16_t *words = (uint16_t*) buf;
+
+    while (count > 0)
+    {
+        size_t amount = count < ATA_MAX_SECTORS ? count : ATA_MAX_SECTORS;
+        if (!ata_wait_ready(drive))
+        {
+            return false;
+        }
+        ata_issue(drive, lba, amount, false);
+
+        for (size_t i = 0; i < amount; ++i)
+        {
+            if (!ata_wait_drq(drive))
+            {
+                return false;
+            }
+            for (size_t j = 0; j < ATA_SECTOR_SIZE / 2; ++j)
+            {
+                *words++ = inw(drive->io + ATA_REG_DATA);
+            }
+        }
+
+        lba += amount;
+        count -= amount;
+    }
+    return true;
+}
+
+static bool ata_write(block_device_t *dev, uint64_t lba, size_t count, const void *buf)
+{
+    ata_drive_t *drive = (ata_drive_t*) dev->data;
+    const uint16_t *words = (const uint16_t*) buf;
+
+    while (count > 0)
+    {
+        size_t amount = count < ATA_MAX_SECTORS ? count : ATA_MAX_SECTORS;
+        if (!ata_wait_ready(drive))
+        {
+            return false;
+        }
+        ata_issue(drive, lba, amount, true);
+
+        for (size_t i = 0; i < amount; ++i)
+        {
+            if (!ata_wait_drq(drive))
+            {
+                return false;
+            }
+            for (size_t j = 0; j < ATA_SECTOR_SIZE / 2; ++j)
+            {
+                outw(drive->io + ATA_REG_DATA, *words++);
+            }
+        }
+
+        lba += amount;
+        count -= amount;
+    }
+
+    outb(drive->io + ATA_REG_COMMAND, drive->lba48 ? ATA_CMD_CACHE_FLUSH_EXT : ATA_CMD_CACHE_FLUSH);
+    return ata_wait_ready(drive);
+}
+
+static bool ata_identify(ata_drive_t *drive, uint16_t *ident)
+{
+    ata_select(drive, ATA_DRIVE_CHS);
+    outb(drive->io + ATA_REG_SECCOUNT, 0);
+    outb(drive->io + ATA_REG_LBA_LO, 0);
+    outb(drive->io + ATA_REG_LBA_MID, 0);
+    outb(drive->io + ATA_REG_LBA_HI, 0);
+    outb(drive->io + ATA_REG_COMMAND, ATA_CMD_IDENTIFY);
+
+    // 0 means no drive, 0xFF is a floating bus with no controller at all
+    uint8_t status = inb(drive->io + ATA_REG_STATUS);
+    if (status == 0 || status == 0xFF)
+    {
+        return false;
+    }
+
+    if (!ata_wait_idle(drive, &status))
+    {
+        return false;
+    }
+
+    // ATAPI and SATA devices set the signature in the LBA registers, leave them alone
+    if (inb(drive->io + ATA_REG_LBA_MID) || inb(drive->io + ATA_REG_LBA_HI))
+    {
+        return false;
+    }
+
+    if (!ata_wait_drq(drive))
+    {
+        return false;
+    }
+
+    for (int i = 0; i < 256; ++i)
+    {
+        ident[i] = inw(drive->io + ATA_REG_DATA);
+    }
+    return true;
+}
+
+static bool ata_probe(ata_drive_t *drive, uint16_t io, uint16_t ctrl, bool slave)
+{
+    uint16_t ident[256];
+
+    drive->io = io;
+    drive->ctrl = ctrl;
+    drive->slave = slave;
+
+    // We poll, keep the drive from raising IRQs we don't handle
+    outb(ctrl, ATA_CTRL_NIEN);
+    if (!ata_identify(drive, ident))
+    {
+        return false;
+    }
+
+    // Model string is stored as big endian words
+    for (int i = 0; i < ATA_IDENT_MODEL_WORDS; ++i)
+    {
+        drive->model[i * 2] = ident[ATA_IDENT_MODEL + i] >> 8;
+        drive->model[i * 2 + 1] = ident[ATA_IDENT_MODEL + i] & 0xFF;
+    }
+    int end = ATA_IDENT_MODEL_WORDS * 2;
+    while (end > 0 && drive->model[end - 1] == ' ')
+    {
+        --end;
+    }
+    drive->model[end] = '\0';
+
+    drive->lba48 = ident[ATA_IDENT_COMMAND_SETS] & ATA_COMMAND_SET_LBA48;
+    uint64_t sectors;
+    if (drive->lba48)
+    {
+        sectors = (uint64_t) ident[ATA_IDENT_LBA48_SECTORS]
+            | (uint64_t) ident[ATA_IDENT_LBA48_SECTORS + 1] << 16
+            | (uint64_t) ident[ATA_IDENT_LBA48_SECTORS + 2] << 32
+            | (uint64_t) ident[ATA_IDENT_LBA48_SECTORS + 3] << 48;
+    }
+    else
+    {
+        sectors = (uint32_t) ident[ATA_IDENT_LBA28_SECTORS]
+            | (uint32_t) ident[ATA_IDENT_LBA28_SECTORS + 1] << 16;
+    }
+
+    drive->dev.sector_size = ATA_SECTOR_SIZE;
+    drive->dev.sector_count = sectors;
+    drive->dev.read = ata_read;
+    drive->dev.write = ata_write;
+    drive->dev.data = drive;
+    return sectors != 0;
+}
+
+size_t ata_init(void)
+{
+    static const uint16_t io_ports[2] = { ATA_PRIMARY_IO, ATA_SECONDARY_IO };
+    static const uint16_t ctrl_ports[2] = { ATA_PRIMARY_CTRL, ATA_SECONDARY_CTRL };
+
+    size_t found = 0;
+    for (int i = 0; i < ATA_MAX_DRIVES; ++i)
+    {
+        ata_drive_t *drive = &drives[found];
+        if (!ata_probe(drive, io_ports[i / 2], ctrl_ports[i / 2], i % 2))
+        {
+            continue;
+        }
+
+        drive->dev.name = drive_names[found];
+        if (block_register(&drive->dev))
+        {
+            klog(LOG_INFO, "ata", "%s: %s, %d MiB", drive->dev.name, drive->model, (int) (drive->dev.sector_count / 2048));
+            ++found;
+        }
+    }
+    return found;
+}
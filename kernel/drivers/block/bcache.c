@@ -0,0 +1,315 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <drivers/block/bcache.h>
+#include <drivers/block/block.h>
+#include <libk/string.h>
+
+typedef struct bcache_buffer_t
+{
+    block_device_t *dev;
+    uint64_t lba;
+    bool dirty;
+    uint32_t last_used;
+    uint8_t data[BCACHE_SECTOR_SIZE];
+} bcache_buffer_t;
+
+static bcache_buffer_t buffers[BCACHE_BUFFERS];
+static uint32_t use_clock;
+
+// Staging area for writing a run of consecutive dirty sectors at once
+static uint8_t run_data[BCACHE_MAX_RUN * BCACHE_SECTOR_SIZE];
+
+static bcache_buffer_t *lookup(block_device_t *dev, uint64_t lba)
+{
+    for (size_t i = 0; i < BCACHE_BUFFERS; ++i)
+    {
+        if (buffers[i].dev == dev && buffers[i].lba == lba)
+        {
+            return &buffers[i];
+        }
+    }
+    return NULL;
+}
+
+// Write back the run of dirty sectors starting at buffer
+static bool flush_run(bcache_buffer_t *buffer)
+{
+    size_t count = 0;
+    bcache_buffer_t *next = buffer;
+    while (next && next->dirty && count < BCACHE_MAX_RUN)
+    {
+        memcpy(run_data + count * BCACHE_SECTOR_SIZE, next->data, BCACHE_SECTOR_SIZE);
+        ++count;
+        next = lookup(buffer->dev, buffer->lba + count);
+    }
+
+    if (!block_write(buffer->dev, buffer->lba, count, run_data))
+    {
+        return false;
+    }
+    for (size_t i = 0; i < count; ++i)
+    {
+        lookup(buffer->dev, buffer->lba + i)->dirty = false;
+    }
+    return true;
+}
+
+// Write back the dirty run this sector belongs to, a chunk at a time from its start
+// until the sector itself is clean
+static bool flush(bcache_buffer_t *buffer)
+{
+    while (buffer->dirty)
+    {
+        bcache_buffer_t *start = buffer;
+        while (start->lba > 0)
+        {
+            bcache_buffer_t *prev = lookup(start->dev, start->lba - 1);
+            if (!prev || !prev->dirty)
+            {
+                break;
+            }
+            start = prev;
+        }
+
+        if (!flush_run(start))
+        {
+            return false;
+        }
+    }
+    return true;
+}
+
+// Find a free buffer, evicting the least recently used one if needed
+static bcache_buffer_t *acquire(block_device_t *dev, uint64_t lba)
+{
+    bcache_buffer_t *victim = &buffers[0];
+    for (size_t i = 0; i < BCACHE_BUFFERS; ++i)
+    {
+        if (!buffers[i].dev)
+        {
+            victim = &buffers[i];
+            break;
+        }
+        if (buffers[i].last_used < victim->last_used)
+        {
+            victim = &buffers[i];
+        }
+    }
+
+    if (victim->dev && victim->dirty && !flush(victim))
+    {
+        return NULL;
+    }
+    victim->dev = dev;
+    victim->lba = lba;
+    victim->dirty = false;
+    return victim;
+}
+
+static void touch(bcache_buffer_t *buffer)
+{
+    buffer->last_used = ++use_clock;
+}
+
+static bool cacheable(block_device_t *dev)
+{
+    return dev->sector_size == BCACHE_SECTOR_SIZE;
+}
+
+bool bcache_read(block_device_t *dev, uint64_t lba, size_t count, void *buf)
+{
+    if (!cacheable(dev))
+    {
+        return block_read(dev, lba, count, buf);
+    }
+
+    uint8_t *out = buf;
+    for (size_t i = 0; i < count; ++i)
+    {
+        bcache_buffer_t *buffer = lookup(dev, lba + i);
+        if (!buffer)
+        {
+            buffer = acquire(dev, lba + i);
+            if (!buffer)
+            {
+                return false;
+            }
+            if (!block_read(dev, lba + i, 1, buffer->data))
+            {
+                buffer->dev = NULL;
+                return false;
+            }
+        }
+        touch(buffer);
+        memcpy(out + i * BCACHE_SECTOR_SIZE, buffer->data, BCACHE_SECTOR_SIZE);
+    }
+    return true;
+}
+
+bool bcache_write(block_device_t *dev, uint64_t lba, size_t count, const void *buf)
+{
+    if (!cacheable(dev))
+    {
+        return block_write(dev, lba, count, buf);
+    }
+    if (lba >= dev->sector_count || count > dev->sector_count - lba || !dev->write)
+    {
+        return false;
+    }
+
+    const uint8_t *in = buf;
+    for (size_t i = 0; i < count; ++i)
+    {
+        // Whole sectors are written, so a miss never needs to read the old contents
+        bcache_buffer_t *buffer = lookup(dev, lba + i);
+        if (!buffer)
+        {
+            buffer = acquire(dev, lba + i);
+            if (!buffer)
+            {
+                return false;
+            }
+        }
+        touch(buffer);
+        memcpy(buffer->data, in + i * BCACHE_SECTOR_SIZE, BCACHE_SECTOR_SIZE);
+        buffer->dirty = true;
+    }
+    return true;
+}
+
+bool bcache_sync(block_device_t *dev)
+{
+    bool ok = true;
+    for (size_t i = 0; i < BCACHE_BUFFERS; ++i)
+    {
+        if (buffers[i].dev && buffers[i].dirty && (!dev || buffers[i].dev == dev))
+        {
+            ok = flush(&buffers[i]) && ok;
+        }
+    }
+    return ok;
+}
+
+void bcache_invalidate(block_device_t *dev)
+{
+    for (size_t i = 0; i < BCACHE_BUFFERS; ++i)
+    {
+        if (buffers[i].dev == dev)
+        {
+            buffers[i].dev = NULL;
+            buffers[i].dirty = false;
+        }
+    }
+}
+
+#ifdef KERNEL_TEST
+#include <test.h>
+
+#define TEST_DISK_SECTORS (BCACHE_BUFFERS * 2)
+
+static uint8_t test_disk[TEST_DISK_SECTORS][BCACHE_SECTOR_SIZE];
+static size_t test_writes;
+static size_t test_written;
+
+static bool test_disk_read(block_device_t *dev, uint64_t lba, size_t count, void *buf)
+{
+    (void) dev;
+    memcpy(buf, test_disk[lba], count * BCACHE_SECTOR_SIZE);
+    return true;
+}
+
+static bool test_disk_write(block_device_t *dev, uint64_t lba, size_t count, const void *buf)
+{
+    (void) dev;
+    memcpy(test_disk[lba], buf, count * BCACHE_SECTOR_SIZE);
+    ++test_writes;
+    test_written += count;
+    return true;
+}
+
+static block_device_t test_dev =
+{
+    "bcache-test", BCACHE_SECTOR_SIZE, TEST_DISK_SECTORS, test_disk_read, test_disk_write, NULL
+};
+
+static void test_reset(void)
+{
+    bcache_invalidate(&test_dev);
+    memset(test_disk, 0, sizeof(test_disk));
+    test_writes = 0;
+    test_written = 0;
+}
+
+// Fill sectors with a byte derived from their LBA
+static void test_pattern(uint8_t *buf, uint64_t lba, size_t count)
+{
+    for (size_t i = 0; i < count; ++i)
+    {
+        memset(buf + i * BCACHE_SECTOR_SIZE, (uint8_t) (lba + i + 1), BCACHE_SECTOR_SIZE);
+    }
+}
+
+static bool test_disk_holds(uint64_t lba, size_t count)
+{
+    for (size_t i = 0; i < count; ++i)
+    {
+        for (size_t j = 0; j < BCACHE_SECTOR_SIZE; ++j)
+        {
+            if (test_disk[lba + i][j] != (uint8_t) (lba + i + 1))
+            {
+                return false;
+            }
+        }
+    }
+    return true;
+}
+
+TEST_CASE(bcache_sync_coalesces_runs)
+{
+    static uint8_t buf[BCACHE_MAX_RUN * 2 * BCACHE_SECTOR_SIZE];
+    test_reset();
+
+    test_pattern(buf, 4, 4);
+    TEST_ASSERT(bcache_write(&test_dev, 4, 4, buf));
+    TEST_ASSERT(test_writes == 0 && !test_disk_holds(4, 1));
+    TEST_ASSERT(bcache_sync(&test_dev));
+    TEST_ASSERT(test_writes == 1 && test_written == 4);
+    TEST_ASSERT(test_disk_holds(4, 4));
+
+    // Nothing left to write back
+    TEST_ASSERT(bcache_sync(NULL));
+    TEST_ASSERT(test_writes == 1);
+
+    // A run longer than BCACHE_MAX_RUN goes out in full chunks
+    test_pattern(buf, 32, BCACHE_MAX_RUN * 2);
+    TEST_ASSERT(bcache_write(&test_dev, 32, BCACHE_MAX_RUN * 2, buf));
+    TEST_ASSERT(bcache_sync(&test_dev));
+    TEST_ASSERT(test_writes == 3 && test_written == 4 + BCACHE_MAX_RUN * 2);
+    TEST_ASSERT(test_disk_holds(32, BCACHE_MAX_RUN * 2));
+    test_reset();
+}
+
+TEST_CASE(bcache_evicts_long_dirty_run)
+{
+    static uint8_t buf[BCACHE_BUFFERS * BCACHE_SECTOR_SIZE];
+    uint8_t sector[BCACHE_SECTOR_SIZE];
+    test_reset();
+
+    test_pattern(buf, 0, 32);
+    TEST_ASSERT(bcache_write(&test_dev, 0, 32, buf));
+
+    // Touch everything but LBA 20 so it becomes the oldest buffer, past the first chunk of its run
+    TEST_ASSERT(bcache_read(&test_dev, 0, 20, buf));
+    TEST_ASSERT(bcache_read(&test_dev, 21, 11, buf));
+
+    // Reading a cache's worth of other sectors evicts the whole run
+    TEST_ASSERT(bcache_read(&test_dev, BCACHE_BUFFERS, BCACHE_BUFFERS, buf));
+    TEST_ASSERT(test_disk_holds(0, 32));
+
+    TEST_ASSERT(bcache_read(&test_dev, 20, 1, sector));
+    TEST_ASSERT(sector[0] == 21 && sector[BCACHE_SECTOR_SIZE - 1] == 21);
+    test_reset();
+}
+#endif
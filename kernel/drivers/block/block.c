@@ -0,0 +1,111 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <dev/device.h>
+#include <drivers/block/block.h>
+#include <libk/string.h>
+
+static block_device_t *devices[BLOCK_MAX_DEVICES];
+static device_t nodes[BLOCK_MAX_DEVICES];
+static size_t num_devices;
+
+static bool node_range(block_device_t *dev, uint64_t offset, size_t len, uint64_t *lba, size_t *count)
+{
+    if (offset % dev->sector_size || len % dev->sector_size)
+    {
+        return false;
+    }
+
+    *lba = offset / dev->sector_size;
+    *count = len / dev->sector_size;
+    return true;
+}
+
+static int node_read(device_t *node, uint64_t offset, void *buf, size_t len)
+{
+    block_device_t *dev = (block_device_t*) node->data;
+    uint64_t lba;
+    size_t count;
+    if (!node_range(dev, offset, len, &lba, &count))
+    {
+        return DEVICE_EINVAL;
+    }
+    return block_read(dev, lba, count, buf) ? (int) len : DEVICE_EIO;
+}
+
+static int node_write(device_t *node, uint64_t offset, const void *buf, size_t len)
+{
+    block_device_t *dev = (block_device_t*) node->data;
+    uint64_t lba;
+    size_t count;
+    if (!node_range(dev, offset, len, &lba, &count))
+    {
+        return DEVICE_EINVAL;
+    }
+    return block_write(dev, lba, count, buf) ? (int) len : DEVICE_EIO;
+}
+
+bool block_register(block_device_t *dev)
+{
+    if (num_devices >= BLOCK_MAX_DEVICES || block_find(dev->name))
+    {
+        return false;
+    }
+
+    device_t *node = &nodes[num_devices];
+    node->name = dev->name;
+    node->type = DEVICE_BLOCK;
+    node->read = node_read;
+    node->write = node_write;
+    node->data = dev;
+    device_register(node);
+
+    devices[num_devices++] = dev;
+    return true;
+}
+
+size_t block_count(void)
+{
+    return num_devices;
+}
+
+block_device_t *block_get(size_t index)
+{
+    return index < num_devices ? devices[index] : NULL;
+}
+
+block_device_t *block_find(const char *name)
+{
+    for (size_t i = 0; i < num_devices; ++i)
+    {
+        if (strcmp(devices[i]->name, name) == 0)
+        {
+            return devices[i];
+        }
+    }
+    return NULL;
+}
+
+static bool in_range(block_device_t *dev, uint64_t lba, size_t count)
+{
+    return lba < dev->sector_count && count <= dev->sector_count - lba;
+}
+
+bool block_read(block_device_t *dev, uint64_t lba, size_t count, void *buf)
+{
+    if (!in_range(dev, lba, count))
+    {
+        return false;
+    }
+    return count == 0 || dev->read(dev, lba, count, buf);
+}
+
+bool block_write(block_device_t *dev, uint64_t lba, size_t count, const void *buf)
+{
+    if (!in_range(dev, lba, count) || !dev->write)
+    {
+        return false;
+    }
+    return count == 0 || dev->write(dev, lba, count, buf);
+}
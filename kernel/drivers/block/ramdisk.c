@@ -0,0 +1,79 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <drivers/block/block.h>
+#include <drivers/block/ramdisk.h>
+#include <libk/string.h>
+
+#define RAMDISK_MAX 4
+
+static block_device_t ramdisks[RAMDISK_MAX];
+static size_t num_ramdisks;
+static const char *ramdisk_names[RAMDISK_MAX] = { "ram0", "ram1", "ram2", "ram3" };
+
+static bool ramdisk_read(block_device_t *dev, uint64_t lba, size_t count, void *buf)
+{
+    uint8_t *base = (uint8_t*) dev->data;
+    memcpy(buf, base + lba * RAMDISK_SECTOR_SIZE, count * RAMDISK_SECTOR_SIZE);
+    return true;
+}
+
+static bool ramdisk_write(block_device_t *dev, uint64_t lba, size_t count, const void *buf)
+{
+    uint8_t *base = (uint8_t*) dev->data;
+    memcpy(base + lba * RAMDISK_SECTOR_SIZE, buf, count * RAMDISK_SECTOR_SIZE);
+    return true;
+}
+
+block_device_t *ramdisk_create(void *base, size_t size)
+{
+    if (num_ramdisks >= RAMDISK_MAX || size < RAMDISK_SECTOR_SIZE)
+    {
+        return NULL;
+    }
+
+    block_device_t *dev = &ramdisks[num_ramdisks];
+    dev->name = ramdisk_names[num_ramdisks];
+    dev->sector_size = RAMDISK_SECTOR_SIZE;
+    dev->sector_count = size / RAMDISK_SECTOR_SIZE;
+    dev->read = ramdisk_read;
+    dev->write = ramdisk_write;
+    dev->data = base;
+
+    if (!block_register(dev))
+    {
+        return NULL;
+    }
+
+    ++num_ramdisks;
+    return dev;
+}
+
+#ifdef KERNEL_TEST
+#include <test.h>
+
+TEST_CASE(ramdisk_reads_and_writes_through_block_layer)
+{
+    static uint8_t disk[RAMDISK_SECTOR_SIZE * 2 + 100];
+    uint8_t sector[RAMDISK_SECTOR_SIZE];
+
+    memset(disk, 0xAA, RAMDISK_SECTOR_SIZE);
+    memset(disk + RAMDISK_SECTOR_SIZE, 0x55, RAMDISK_SECTOR_SIZE);
+    block_device_t *dev = ramdisk_create(disk, sizeof(disk));
+    TEST_ASSERT(dev);
+    TEST_ASSERT(block_find(dev->name) == dev);
+    // The partial trailing sector isn't exposed
+    TEST_ASSERT(dev->sector_count == 2);
+
+    TEST_ASSERT(block_read(dev, 1, 1, sector));
+    TEST_ASSERT(sector[0] == 0x55 && sector[RAMDISK_SECTOR_SIZE - 1] == 0x55);
+
+    memset(sector, 0x11, sizeof(sector));
+    TEST_ASSERT(block_write(dev, 0, 1, sector));
+    TEST_ASSERT(disk[0] == 0x11 && disk[RAMDISK_SECTOR_SIZE - 1] == 0x11 && disk[RAMDISK_SECTOR_SIZE] == 0x55);
+
+    TEST_ASSERT(!block_read(dev, 2, 1, sector));
+    TEST_ASSERT(!ramdisk_create(disk, RAMDISK_SECTOR_SIZE - 1));
+}
+#endif
@@ -0,0 +1,73 @@
+#include <stddef.h>
+#include <stdint.h>
+
+#include <dev/device.h>
+#include <drivers/char/mem.h>
+#include <libk/string.h>
+
+// Reads hit EOF straight away, writes are thrown away
+static int null_read(device_t *dev, uint64_t offset, void *buf, size_t len)
+{
+    (void) dev;
+    (void) offset;
+    (void) buf;
+    (void) len;
+    return 0;
+}
+
+static int null_write(device_t *dev, uint64_t offset, const void *buf, size_t len)
+{
+    (void) dev;
+    (void) offset;
+    (void) buf;
+    return len;
+}
+
+static int zero_read(device_t *dev, uint64_t offset, void *buf, size_t len)
+{
+    (void) dev;
+    (void) offset;
+    memset(buf, 0, len);
+    return len;
+}
+
+// Always out of space
+static int full_write(device_t *dev, uint64_t offset, const void *buf, size_t len)
+{
+    (void) dev;
+    (void) offset;
+    (void) buf;
+    (void) len;
+    return DEVICE_ENOSPC;
+}
+
+static device_t null_device =
+{
+    .name = "null",
+    .type = DEVICE_CHAR,
+    .read = null_read,
+    .write = null_write,
+};
+
+static device_t zero_device =
+{
+    .name = "zero",
+    .type = DEVICE_CHAR,
+    .read = zero_read,
+    .write = null_write,
+};
+
+static device_t full_device =
+{
+    .name = "full",
+    .type = DEVICE_CHAR,
+    .read = zero_read,
+    .write = full_write,
+};
+
+void mem_devices_init(void)
+{
+    device_register(&null_device);
+    device_register(&zero_device);
+    device_register(&full_device);
+}
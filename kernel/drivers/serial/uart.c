@@ -0,0 +1,297 @@
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <cpu/interrupts.h>
+#include <cpu/ports.h>
+#include <dev/device.h>
+#include <drivers/serial/uart.h>
+#include <libk/string.h>
+
+#define UART_CLOCK 115200
+
+// Register offsets from the base port
+#define UART_DATA 0
+#define UART_INT_ENABLE 1
+#define UART_DIVISOR_LO 0
+#define UART_DIVISOR_HI 1
+#define UART_FIFO_CTRL 2
+#define UART_LINE_CTRL 3
+#define UART_MODEM_CTRL 4
+#define UART_LINE_STATUS 5
+
+#define UART_IER_RX (1 << 0)
+#define UART_LCR_STOP_2 (1 << 2)
+#define UART_LCR_PARITY_ODD 0x08
+#define UART_LCR_PARITY_EVEN 0x18
+#define UART_LCR_DLAB (1 << 7)
+#define UART_FCR_ENABLE 0xC7
+#define UART_MCR_NORMAL 0x0F
+#define UART_MCR_LOOPBACK 0x1E
+#define UART_LSR_DATA_READY (1 << 0)
+#define UART_LSR_THR_EMPTY (1 << 5)
+
+static const uint16_t serial_ports[SERIAL_PORTS] = { COM1, COM2, COM3, COM4 };
+static bool serial_ready[SERIAL_PORTS];
+static device_t serial_devices[SERIAL_PORTS];
+static const char *serial_names[SERIAL_PORTS] = { "com1", "com2", "com3", "com4" };
+static const uint8_t serial_irqs[SERIAL_PORTS] = { 4, 3, 4, 3 };
+static serial_rx_handler_t rx_handlers[SERIAL_PORTS];
+
+static const serial_config_t default_config =
+{
+    .baud = SERIAL_DEFAULT_BAUD,
+    .data_bits = 8,
+    .parity = SERIAL_PARITY_NONE,
+    .stop_bits = 1,
+};
+
+static bool serial_valid(int port)
+{
+    return port >= 0 && port < SERIAL_PORTS;
+}
+
+// Check the chip is actually there by echoing a byte through loopback mode
+static bool serial_probe(uint16_t base)
+{
+    outb(base + UART_INT_ENABLE, 0x00);
+    outb(base + UART_MODEM_CTRL, UART_MCR_LOOPBACK);
+    outb(base + UART_DATA, 0xAE);
+    if (inb(base + UART_DATA) != 0xAE)
+    {
+        return false;
+    }
+
+    outb(base + UART_MODEM_CTRL, UART_MCR_NORMAL);
+    return true;
+}
+
+// Reads only return what has already arrived, they never wait for input
+static int serial_dev_read(device_t *dev, uint64_t offset, void *buf, size_t len)
+{
+    (void) offset;
+    int port = (int) (uintptr_t) dev->data;
+    char *out = (char*) buf;
+    size_t count = 0;
+    while (count < len && serial_received(port))
+    {
+        out[count++] = serial_getc(port);
+    }
+    return count;
+}
+
+static int serial_dev_write(device_t *dev, uint64_t offset, const void *buf, size_t len)
+{
+    (void) offset;
+    serial_write((int) (uintptr_t) dev->data, (const char*) buf, len);
+    return len;
+}
+
+void serial_init(void)
+{
+    for (int i = 0; i < SERIAL_PORTS; ++i)
+    {
+        serial_ready[i] = serial_probe(serial_ports[i]);
+        if (serial_ready[i])
+        {
+            serial_configure(i, &default_config);
+
+            serial_devices[i].name = serial_names[i];
+            serial_devices[i].type = DEVICE_CHAR;
+            serial_devices[i].read = serial_dev_read;
+            serial_devices[i].write = serial_dev_write;
+            serial_devices[i].data = (void*) (uintptr_t) i;
+            device_register(&serial_devices[i]);
+        }
+    }
+}
+
+bool serial_present(int port)
+{
+    return serial_valid(port) && serial_ready[port];
+}
+
+bool serial_configure(int port, const serial_config_t *config)
+{
+    // The divisor is 16 bits and only exact rates are accepted, anything else would be silently rounded
+    if (!serial_present(port) || config->baud == 0 || config->baud > UART_CLOCK
+        || UART_CLOCK % config->baud != 0 || UART_CLOCK / config->baud > 0xFFFF
+        || config->data_bits < 5 || config->data_bits > 8
+        || config->stop_bits < 1 || config->stop_bits > 2)
+    {
+        return false;
+    }
+
+    uint16_t base = serial_ports[port];
+    uint16_t divisor = UART_CLOCK / config->baud;
+
+    uint8_t line = config->data_bits - 5;
+    if (config->stop_bits == 2)
+    {
+        line |= UART_LCR_STOP_2;
+    }
+    if (config->parity == SERIAL_PARITY_ODD)
+    {
+        line |= UART_LCR_PARITY_ODD;
+    }
+    else if (config->parity == SERIAL_PARITY_EVEN)
+    {
+        line |= UART_LCR_PARITY_EVEN;
+    }
+
+    outb(base + UART_LINE_CTRL, UART_LCR_DLAB);
+    outb(base + UART_DIVISOR_LO, divisor & 0xFF);
+    outb(base + UART_DIVISOR_HI, (divisor >> 8) & 0xFF);
+    outb(base + UART_LINE_CTRL, line);
+    outb(base + UART_FIFO_CTRL, UART_FCR_ENABLE);
+    return true;
+}
+
+bool serial_parse(const char *spec, int *port, serial_config_t *config)
+{
+    if (strncmp(spec, "ttyS", 4) != 0 || spec[4] < '0' || spec[4] > '9')
+    {
+        return false;
+    }
+    spec += 4;
+
+    *port = 0;
+    while (*spec >= '0' && *spec <= '9')
+    {
+        *port = *port * 10 + (*spec++ - '0');
+        if (*port >= SERIAL_PORTS)
+        {
+            return false;
+        }
+    }
+    if (!serial_valid(*port))
+    {
+        return false;
+    }
+
+    *config = default_config;
+    if (*spec != ',')
+    {
+        return *spec == '\0';
+    }
+    ++spec;
+
+    if (*spec >= '0' && *spec <= '9')
+    {
+        config->baud = 0;
+        while (*spec >= '0' && *spec <= '9')
+        {
+            config->baud = config->baud * 10 + (*spec++ - '0');
+            if (config->baud > UART_CLOCK)
+            {
+                return false;
+            }
+        }
+    }
+
+    if (*spec == 'n')
+    {
+        config->parity = SERIAL_PARITY_NONE;
+        ++spec;
+    }
+    else if (*spec == 'o')
+    {
+        config->parity = SERIAL_PARITY_ODD;
+        ++spec;
+    }
+    else if (*spec == 'e')
+    {
+        config->parity = SERIAL_PARITY_EVEN;
+        ++spec;
+    }
+
+    if (*spec >= '5' && *spec <= '8')
+    {
+        config->data_bits = *spec++ - '0';
+    }
+
+    return *spec == '\0';
+}
+
+bool serial_received(int port)
+{
+    return serial_present(port) && (inb(serial_ports[port] + UART_LINE_STATUS) & UART_LSR_DATA_READY);
+}
+
+char serial_getc(int port)
+{
+    while (!serial_received(port))
+    {
+    }
+
+    return inb(serial_ports[port] + UART_DATA);
+}
+
+void serial_putc(int port, char c)
+{
+    if (!serial_present(port))
+    {
+        return;
+    }
+
+    uint16_t base = serial_ports[port];
+    while (!(inb(base + UART_LINE_STATUS) & UART_LSR_THR_EMPTY))
+    {
+    }
+
+    outb(base + UART_DATA, c);
+}
+
+void serial_write(int port, const char *data, size_t len)
+{
+    for (size_t i = 0; i < len; ++i)
+    {
+        serial_putc(port, data[i]);
+    }
+}
+
+// COM1/COM3 and COM2/COM4 share a line, drain every port with a handler
+static void serial_irq(interrupt_registers_t *regs, void *data)
+{
+    (void) regs;
+    (void) data;
+    for (int i = 0; i < SERIAL_PORTS; ++i)
+    {
+        if (!rx_handlers[i])
+        {
+            continue;
+        }
+        while (serial_received(i))
+        {
+            rx_handlers[i](i, inb(serial_ports[i] + UART_DATA));
+        }
+    }
+}
+
+bool serial_set_rx_handler(int port, serial_rx_handler_t handler)
+{
+    if (!serial_present(port))
+    {
+        return false;
+    }
+
+    if (handler && !irq_register_handler(serial_irqs[port], serial_irq, NULL, "serial"))
+    {
+        return false;
+    }
+
+    rx_handlers[port] = handler;
+    outb(serial_ports[port] + UART_INT_ENABLE, handler ? UART_IER_RX : 0x00);
+    if (!handler)
+    {
+        // Release the line once no port sharing it is listening anymore
+        for (int i = 0; i < SERIAL_PORTS; ++i)
+        {
+            if (rx_handlers[i] && serial_irqs[i] == serial_irqs[port])
+            {
+                return true;
+            }
+        }
+        irq_free(serial_irqs[port]);
+    }
+    return true;
+}
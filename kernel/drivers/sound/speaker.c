@@ -0,0 +1,33 @@
+#include <stdint.h>
+
+#include <cpu/ports.h>
+#include <drivers/sound/speaker.h>
+#include <drivers/timer/pit.h>
+#include <time/time.h>
+
+#define SPEAKER_PORT 0x61
+#define SPEAKER_GATE 0x03 // PIT channel 2 gate and speaker data enable
+
+void speaker_on(uint32_t freq)
+{
+    if (freq == 0)
+    {
+        speaker_off();
+        return;
+    }
+
+    pit_square_wave(freq);
+    outb(SPEAKER_PORT, inb(SPEAKER_PORT) | SPEAKER_GATE);
+}
+
+void speaker_off(void)
+{
+    outb(SPEAKER_PORT, inb(SPEAKER_PORT) & ~SPEAKER_GATE);
+}
+
+void speaker_beep(uint32_t freq, uint32_t ms)
+{
+    speaker_on(freq);
+    mdelay(ms);
+    speaker_off();
+}
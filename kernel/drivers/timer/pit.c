@@ -0,0 +1,132 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <cpu/interrupts.h>
+#include <cpu/ports.h>
+#include <drivers/timer/pit.h>
+#include <sync/seqlock.h>
+
+#define PIT_CHANNEL_0 0x40
+#define PIT_CHANNEL_2 0x42
+#define PIT_COMMAND 0x43
+
+#define PIT_CH0_PERIODIC 0x34 // Channel 0, lobyte/hibyte, mode 2
+#define PIT_CH2_ONESHOT 0xB0 // Channel 2, lobyte/hibyte, mode 0
+#define PIT_CH2_SQUARE_WAVE 0xB6 // Channel 2, lobyte/hibyte, mode 3
+
+#define PIT_GATE 0x61
+#define PIT_GATE_CH2 (1 << 0)
+#define PIT_GATE_SPEAKER (1 << 1)
+#define PIT_GATE_CH2_OUT (1 << 5)
+
+static uint64_t ticks;
+static seqlock_t ticks_lock = SEQLOCK_INIT;
+static uint8_t saved_gate;
+static pit_tick_callback_t tick_callbacks[PIT_MAX_TICK_CALLBACKS];
+
+static void pit_tick(interrupt_registers_t *regs, void *data)
+{
+    (void) regs;
+    (void) data;
+    seqlock_write_begin(&ticks_lock);
+    uint64_t now = ++ticks;
+    seqlock_write_end(&ticks_lock);
+
+    for (int i = 0; i < PIT_MAX_TICK_CALLBACKS; ++i)
+    {
+        if (tick_callbacks[i])
+        {
+            tick_callbacks[i](now);
+        }
+    }
+}
+
+void pit_init(void)
+{
+    uint16_t divisor = PIT_FREQUENCY / PIT_TICK_HZ;
+
+    outb(PIT_COMMAND, PIT_CH0_PERIODIC);
+    outb(PIT_CHANNEL_0, divisor & 0xFF);
+    outb(PIT_CHANNEL_0, (divisor >> 8) & 0xFF);
+
+    irq_register_handler(IRQ_TIMER, pit_tick, NULL, "pit");
+}
+
+// The counter is 64 bits wide, re-read if the tick handler updated it mid-read
+uint64_t pit_ticks(void)
+{
+    uint32_t seq;
+    uint64_t now;
+    do
+    {
+        seq = seqlock_read_begin(&ticks_lock);
+        now = ticks;
+    } while (seqlock_read_retry(&ticks_lock, seq));
+    return now;
+}
+
+void pit_sleep(uint32_t ms)
+{
+    uint64_t end = pit_ticks() + (uint64_t) ms * PIT_TICK_HZ / 1000;
+    while (pit_ticks() < end)
+    {
+        __asm__ volatile ("hlt");
+    }
+}
+
+bool pit_register_tick(pit_tick_callback_t callback)
+{
+    for (int i = 0; i < PIT_MAX_TICK_CALLBACKS; ++i)
+    {
+        if (!tick_callbacks[i])
+        {
+            __atomic_store_n(&tick_callbacks[i], callback, __ATOMIC_RELEASE);
+            return true;
+        }
+    }
+    return false;
+}
+
+void pit_countdown_start(uint16_t count)
+{
+    saved_gate = inb(PIT_GATE);
+    outb(PIT_GATE, (saved_gate & ~PIT_GATE_SPEAKER) | PIT_GATE_CH2);
+    outb(PIT_COMMAND, PIT_CH2_ONESHOT);
+    outb(PIT_CHANNEL_2, count & 0xFF);
+    outb(PIT_CHANNEL_2, (count >> 8) & 0xFF);
+}
+
+bool pit_countdown_done(void)
+{
+    return inb(PIT_GATE) & PIT_GATE_CH2_OUT;
+}
+
+void pit_countdown_stop(void)
+{
+    outb(PIT_GATE, saved_gate);
+}
+
+void pit_square_wave(uint32_t hz)
+{
+    if (hz == 0)
+    {
+        return;
+    }
+
+    // A divisor of 0 means 65536 to the PIT, the lowest tone rather than the highest
+    uint32_t divisor = PIT_FREQUENCY / hz;
+    if (divisor == 0)
+    {
+        divisor = 1;
+    }
+    else if (divisor > 0xFFFF)
+    {
+        divisor = 0xFFFF;
+    }
+
+    outb(PIT_COMMAND, PIT_CH2_SQUARE_WAVE);
+    outb(PIT_CHANNEL_2, divisor & 0xFF);
+    outb(PIT_CHANNEL_2, (divisor >> 8) & 0xFF);
+    outb(PIT_GATE, inb(PIT_GATE) | PIT_GATE_CH2);
+}
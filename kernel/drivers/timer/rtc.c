@@ -0,0 +1,134 @@
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <cpu/ports.h>
+#include <drivers/timer/rtc.h>
+#include <libk/string.h>
+#include <time/time.h>
+
+#define CMOS_INDEX 0x70
+#define CMOS_DATA 0x71
+
+#define RTC_SECONDS 0x00
+#define RTC_MINUTES 0x02
+#define RTC_HOURS 0x04
+#define RTC_DAY 0x07
+#define RTC_MONTH 0x08
+#define RTC_YEAR 0x09
+#define RTC_CENTURY 0x32 // Not standard, but where every PC and QEMU put it
+#define RTC_STATUS_A 0x0A
+#define RTC_STATUS_B 0x0B
+
+#define RTC_UPDATING (1 << 7) // Status A
+#define RTC_24_HOUR (1 << 1) // Status B
+#define RTC_BINARY (1 << 2) // Status B
+#define RTC_PM (1 << 7) // Hours in 12 hour mode
+
+#define RTC_RAW_REGISTERS 7
+#define RTC_READ_ATTEMPTS 8
+// An update takes under 2 ms, a missing CMOS reads back as all ones and never finishes
+#define RTC_UPDATE_POLLS 1000
+#define RTC_UPDATE_POLL_US 10
+
+static uint8_t cmos_read(uint8_t reg)
+{
+    // Bit 7 of the index masks NMIs for as long as it stays set, so leave it clear
+    outb(CMOS_INDEX, reg);
+    return inb(CMOS_DATA);
+}
+
+// Waits up to polls times for the once a second update to finish, 0 to not wait at all
+static bool read_raw(uint8_t *raw, int polls)
+{
+    // Don't read in the middle of the update
+    while (cmos_read(RTC_STATUS_A) & RTC_UPDATING)
+    {
+        if (polls-- <= 0)
+        {
+            return false;
+        }
+        udelay(RTC_UPDATE_POLL_US);
+    }
+
+    raw[0] = cmos_read(RTC_SECONDS);
+    raw[1] = cmos_read(RTC_MINUTES);
+    raw[2] = cmos_read(RTC_HOURS);
+    raw[3] = cmos_read(RTC_DAY);
+    raw[4] = cmos_read(RTC_MONTH);
+    raw[5] = cmos_read(RTC_YEAR);
+    raw[6] = cmos_read(RTC_CENTURY);
+    return true;
+}
+
+static int from_bcd(uint8_t value, bool binary)
+{
+    return binary ? value : (value >> 4) * 10 + (value & 0xF);
+}
+
+static void decode(const uint8_t *raw, date_t *date)
+{
+    uint8_t status = cmos_read(RTC_STATUS_B);
+    bool binary = status & RTC_BINARY;
+    bool pm = !(status & RTC_24_HOUR) && (raw[2] & RTC_PM);
+
+    date->second = from_bcd(raw[0], binary);
+    date->minute = from_bcd(raw[1], binary);
+    date->hour = from_bcd(raw[2] & ~RTC_PM, binary);
+    date->day = from_bcd(raw[3], binary);
+    date->month = from_bcd(raw[4], binary);
+    date->year = from_bcd(raw[5], binary);
+
+    // 12 hour mode counts 12, 1, ..., 11
+    if (!(status & RTC_24_HOUR))
+    {
+        date->hour = date->hour % 12 + (pm ? 12 : 0);
+    }
+
+    int century = from_bcd(raw[6], binary);
+    date->year += century >= 19 && century <= 30 ? century * 100 : 2000;
+}
+
+bool rtc_read(date_t *date)
+{
+    uint8_t raw[RTC_RAW_REGISTERS];
+    uint8_t again[RTC_RAW_REGISTERS];
+    bool settled = false;
+
+    // An update can still sneak in between the status check and the reads, so read until two agree
+    if (!read_raw(raw, RTC_UPDATE_POLLS))
+    {
+        return false;
+    }
+    for (int i = 0; i < RTC_READ_ATTEMPTS && !settled; ++i)
+    {
+        if (!read_raw(again, RTC_UPDATE_POLLS))
+        {
+            return false;
+        }
+        settled = true;
+        for (int j = 0; j < RTC_RAW_REGISTERS; ++j)
+        {
+            settled = settled && raw[j] == again[j];
+            raw[j] = again[j];
+        }
+    }
+    if (!settled)
+    {
+        return false;
+    }
+
+    decode(raw, date);
+    return true;
+}
+
+bool rtc_try_read(date_t *date)
+{
+    uint8_t raw[RTC_RAW_REGISTERS];
+    uint8_t again[RTC_RAW_REGISTERS];
+    if (!read_raw(raw, 0) || !read_raw(again, 0) || memcmp(raw, again, sizeof(raw)) != 0)
+    {
+        return false;
+    }
+    decode(raw, date);
+    return true;
+}
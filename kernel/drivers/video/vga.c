@@ -1,62 +1,673 @@
+#include <stdbool.h>
 #include <stdint.h>
 
+#include <cpu.h>
+#include <cpu/ports.h>
+#include <dev/device.h>
+#include <drivers/sound/speaker.h>
 #include <libk/string.h>
+#include <drivers/video/cp437.h>
 #include <drivers/video/vga.h>
+#include <time/timer.h>
 #include <tty/tty.h>
 
+#define ANSI_ESC 0x1B
+#define ANSI_MAX_PARAMS 8
+#define ANSI_MAX_PARAM_VALUE 9999
+
+#define VGA_INPUT_STATUS 0x3DA
+#define VGA_ATTR_INDEX 0x3C0
+#define VGA_ATTR_READ 0x3C1
+#define VGA_ATTR_MODE 0x10
+#define VGA_ATTR_BLINK 0x08
+#define VGA_ATTR_PAS 0x20 // Palette address source, the screen stays blank while it is clear
+
+#define TTY_DEFAULT_TAB_WIDTH 8
+#define TTY_BELL_FREQ 880
+#define TTY_BELL_MS 50
+
+typedef enum ansi_state_t
+{
+    ANSI_NORMAL,
+    ANSI_ESCAPE,
+    ANSI_CSI,
+} ansi_state_t;
+
 static size_t tty_row;
 static size_t tty_col;
+static size_t tty_tab_width = TTY_DEFAULT_TAB_WIDTH;
 static uint8_t tty_color;
+static color_t tty_fg;
+static color_t tty_bg;
+static bool tty_bold;
+static bool tty_inverse;
 static uint16_t *tty_buffer;
+static timer_t bell_timer;
+
+static ansi_state_t ansi_state;
+static int ansi_params[ANSI_MAX_PARAMS];
+static size_t ansi_index;
+static bool ansi_private;
+static size_t saved_row;
+static size_t saved_col;
+
+// Partially decoded UTF-8 sequence
+static uint32_t utf8_codepoint;
+static size_t utf8_remaining;
+static uint32_t utf8_min;
+
+// ANSI color numbers in VGA palette order, the bright variants are 8 entries further on
+static const color_t ansi_colors[8] = { BLACK, RED, GREEN, BROWN, BLUE, MAGENTA, CYAN, LIGHT_GREY };
+
+// RGB values of the default VGA palette, indexed by color_t
+static const uint8_t vga_palette[16][3] =
+{
+    { 0, 0, 0 }, { 0, 0, 170 }, { 0, 170, 0 }, { 0, 170, 170 },
+    { 170, 0, 0 }, { 170, 0, 170 }, { 170, 85, 0 }, { 170, 170, 170 },
+    { 85, 85, 85 }, { 85, 85, 255 }, { 85, 255, 85 }, { 85, 255, 255 },
+    { 255, 85, 85 }, { 255, 85, 255 }, { 255, 255, 85 }, { 255, 255, 255 },
+};
+
+// Channel levels of the 6x6x6 cube in the xterm 256 color palette
+static const uint8_t xterm_levels[6] = { 0, 95, 135, 175, 215, 255 };
+
+// Everything is drawn here first, VGA memory is only touched when flushing
+static uint16_t back_buffer[VGA_WIDTH * VGA_HEIGHT];
+static bool autoflush = true;
+
+// Changed columns per row since the last flush, a row is clean when first > last
+static uint8_t dirty_first[VGA_HEIGHT];
+static uint8_t dirty_last[VGA_HEIGHT];
+
+static int tty_dev_write(device_t *dev, uint64_t offset, const void *buf, size_t len);
+
+static device_t tty_device =
+{
+    .name = "tty0",
+    .type = DEVICE_CHAR,
+    .write = tty_dev_write,
+};
+
+static void mark_dirty(size_t row, size_t first, size_t last)
+{
+    if (first < dirty_first[row])
+    {
+        dirty_first[row] = first;
+    }
+    if (last > dirty_last[row])
+    {
+        dirty_last[row] = last;
+    }
+}
+
+static void mark_clean(size_t row)
+{
+    dirty_first[row] = VGA_WIDTH;
+    dirty_last[row] = 0;
+}
 
 static void vga_printchar(char c, size_t row, size_t col, color_t color)
 {
     size_t index = row * VGA_WIDTH + col;
-    tty_buffer[index] = vga_entry(c, color);
+    back_buffer[index] = vga_entry(c, color);
+    mark_dirty(row, col, col);
+}
+
+static void vga_scroll(void)
+{
+    memmove(back_buffer, back_buffer + VGA_WIDTH, (VGA_HEIGHT - 1) * VGA_WIDTH * sizeof(uint16_t));
+    for (size_t i = 0; i < VGA_WIDTH; ++i)
+    {
+        back_buffer[(VGA_HEIGHT - 1) * VGA_WIDTH + i] = vga_entry('\0', tty_color);
+    }
+    for (size_t i = 0; i < VGA_HEIGHT; ++i)
+    {
+        mark_dirty(i, 0, VGA_WIDTH - 1);
+    }
+}
+
+static void vga_clear(size_t start, size_t end)
+{
+    uint16_t blank = vga_entry(' ', tty_color);
+    for (size_t i = start; i < end; ++i)
+    {
+        back_buffer[i] = blank;
+        mark_dirty(i / VGA_WIDTH, i % VGA_WIDTH, i % VGA_WIDTH);
+    }
+}
+
+static void update_color(void)
+{
+    color_t fg = tty_fg;
+    color_t bg = tty_bg;
+    // Bold is shown as the bright variant of the foreground
+    if (tty_bold && fg < DARK_GREY)
+    {
+        fg += 8;
+    }
+    if (tty_inverse)
+    {
+        color_t tmp = fg;
+        fg = bg;
+        bg = tmp;
+    }
+    tty_color = vga_entry_color(fg, bg);
+}
+
+// Use bit 7 of the attribute as a bright background instead of blinking
+static void vga_disable_blink(void)
+{
+    inb(VGA_INPUT_STATUS);
+    outb(VGA_ATTR_INDEX, VGA_ATTR_MODE | VGA_ATTR_PAS);
+    uint8_t mode = inb(VGA_ATTR_READ);
+    outb(VGA_ATTR_INDEX, mode & ~VGA_ATTR_BLINK);
+}
+
+static void vga_newline(void)
+{
+    tty_col = 0;
+    if (++tty_row >= VGA_HEIGHT)
+    {
+        vga_scroll();
+        tty_row = VGA_HEIGHT - 1;
+    }
+}
+
+static void bell_off(timer_t *timer, void *data)
+{
+    (void) timer;
+    (void) data;
+    speaker_off();
 }
 
 void tty_init(void)
 {
+    timer_setup(&bell_timer, bell_off, NULL);
     tty_row = 0;
     tty_col = 0;
-    tty_color = vga_entry_color(DEFAULT_COLOR, BLACK);
+    tty_fg = DEFAULT_COLOR;
+    tty_bg = BLACK;
+    tty_bold = false;
+    tty_inverse = false;
+    update_color();
     tty_buffer = VGA_BUFFER;
+    vga_disable_blink();
+    ansi_state = ANSI_NORMAL;
+    utf8_remaining = 0;
 
     for (size_t i = 0; i < VGA_HEIGHT; ++i)
     {
         for (size_t j = 0; j < VGA_WIDTH; ++j)
         {
             size_t index = i * VGA_WIDTH + j;
-            tty_buffer[index] = vga_entry('\0', tty_color);
+            back_buffer[index] = vga_entry('\0', tty_color);
         }
+        mark_clean(i);
+        mark_dirty(i, 0, VGA_WIDTH - 1);
     }
+    tty_flush();
+
+    device_register(&tty_device);
 }
 
-void tty_write(const char *data, size_t len)
+// Parameter i, or def if it was left out or given as 0
+static int ansi_param(size_t i, int def)
 {
-    for (size_t i = 0; i < len; ++i)
+    if (i > ansi_index || i >= ANSI_MAX_PARAMS || ansi_params[i] == 0)
+    {
+        return def;
+    }
+    return ansi_params[i];
+}
+
+static size_t clamp(int value, size_t max)
+{
+    if (value < 0)
+    {
+        return 0;
+    }
+    return (size_t) value > max ? max : (size_t) value;
+}
+
+static color_t nearest_color(uint8_t r, uint8_t g, uint8_t b)
+{
+    color_t best = BLACK;
+    uint32_t best_dist = UINT32_MAX;
+    for (size_t i = 0; i < 16; ++i)
     {
-        // TODO: Better handling of special chars
-        if (data[i] == '\n')
+        int dr = r - vga_palette[i][0];
+        int dg = g - vga_palette[i][1];
+        int db = b - vga_palette[i][2];
+        uint32_t dist = dr * dr + dg * dg + db * db;
+        if (dist < best_dist)
         {
-            tty_col = 0;
-            ++tty_row;
+            best = i;
+            best_dist = dist;
         }
-        else
+    }
+    return best;
+}
+
+static color_t xterm_color(int n)
+{
+    if (n < 8)
+    {
+        return ansi_colors[n];
+    }
+    if (n < 16)
+    {
+        return ansi_colors[n - 8] + 8;
+    }
+    if (n < 232)
+    {
+        n -= 16;
+        return nearest_color(xterm_levels[n / 36], xterm_levels[n / 6 % 6], xterm_levels[n % 6]);
+    }
+    uint8_t grey = 8 + (n - 232) * 10;
+    return nearest_color(grey, grey, grey);
+}
+
+// Parse the rest of a 38 or 48 sequence starting at *i, leaving *i on its last parameter.
+// Incomplete or unknown forms consume everything, there is no telling where they end.
+static void ansi_extended_color(size_t *i, size_t count, color_t *color)
+{
+    int mode = *i + 1 < count ? ansi_params[*i + 1] : -1;
+    if (mode == 5 && *i + 2 < count)
+    {
+        *color = xterm_color(clamp(ansi_params[*i + 2], 255));
+        *i += 2;
+    }
+    else if (mode == 2 && *i + 4 < count)
+    {
+        uint8_t r = clamp(ansi_params[*i + 2], 255);
+        uint8_t g = clamp(ansi_params[*i + 3], 255);
+        uint8_t b = clamp(ansi_params[*i + 4], 255);
+        *color = nearest_color(r, g, b);
+        *i += 4;
+    }
+    else
+    {
+        *i = count;
+    }
+}
+
+static void ansi_sgr(void)
+{
+    size_t count = ansi_index < ANSI_MAX_PARAMS ? ansi_index + 1 : ANSI_MAX_PARAMS;
+    for (size_t i = 0; i < count; ++i)
+    {
+        int p = ansi_params[i];
+        if (p == 0)
+        {
+            tty_fg = DEFAULT_COLOR;
+            tty_bg = BLACK;
+            tty_bold = false;
+            tty_inverse = false;
+        }
+        else if (p == 1)
+        {
+            tty_bold = true;
+        }
+        else if (p == 7)
+        {
+            tty_inverse = true;
+        }
+        else if (p == 22)
+        {
+            tty_bold = false;
+        }
+        else if (p == 27)
+        {
+            tty_inverse = false;
+        }
+        else if (p >= 30 && p <= 37)
+        {
+            tty_fg = ansi_colors[p - 30];
+        }
+        else if (p == 38)
+        {
+            ansi_extended_color(&i, count, &tty_fg);
+        }
+        else if (p == 39)
+        {
+            tty_fg = DEFAULT_COLOR;
+        }
+        else if (p >= 40 && p <= 47)
+        {
+            tty_bg = ansi_colors[p - 40];
+        }
+        else if (p == 48)
+        {
+            ansi_extended_color(&i, count, &tty_bg);
+        }
+        else if (p == 49)
+        {
+            tty_bg = BLACK;
+        }
+        else if (p >= 90 && p <= 97)
+        {
+            tty_fg = ansi_colors[p - 90] + 8;
+        }
+        else if (p >= 100 && p <= 107)
+        {
+            tty_bg = ansi_colors[p - 100] + 8;
+        }
+    }
+    update_color();
+}
+
+static void ansi_csi_dispatch(char c)
+{
+    // Private sequences like ESC[?25l have no meaning for this console
+    if (ansi_private)
+    {
+        return;
+    }
+
+    size_t cursor = tty_row * VGA_WIDTH + tty_col;
+    switch (c)
+    {
+        case 'A':
+            tty_row = clamp((int) tty_row - ansi_param(0, 1), VGA_HEIGHT - 1);
+            break;
+        case 'B':
+            tty_row = clamp((int) tty_row + ansi_param(0, 1), VGA_HEIGHT - 1);
+            break;
+        case 'C':
+            tty_col = clamp((int) tty_col + ansi_param(0, 1), VGA_WIDTH - 1);
+            break;
+        case 'D':
+            tty_col = clamp((int) tty_col - ansi_param(0, 1), VGA_WIDTH - 1);
+            break;
+        case 'E':
+            tty_row = clamp((int) tty_row + ansi_param(0, 1), VGA_HEIGHT - 1);
+            tty_col = 0;
+            break;
+        case 'F':
+            tty_row = clamp((int) tty_row - ansi_param(0, 1), VGA_HEIGHT - 1);
+            tty_col = 0;
+            break;
+        case 'G':
+            tty_col = clamp(ansi_param(0, 1) - 1, VGA_WIDTH - 1);
+            break;
+        case 'H':
+        case 'f':
+            tty_row = clamp(ansi_param(0, 1) - 1, VGA_HEIGHT - 1);
+            tty_col = clamp(ansi_param(1, 1) - 1, VGA_WIDTH - 1);
+            break;
+        case 'J':
+            switch (ansi_param(0, 0))
+            {
+                case 0: vga_clear(cursor, VGA_WIDTH * VGA_HEIGHT); break;
+                case 1: vga_clear(0, cursor + 1); break;
+                case 2:
+                case 3: vga_clear(0, VGA_WIDTH * VGA_HEIGHT); break;
+            }
+            break;
+        case 'K':
+            switch (ansi_param(0, 0))
+            {
+                case 0: vga_clear(cursor, (tty_row + 1) * VGA_WIDTH); break;
+                case 1: vga_clear(tty_row * VGA_WIDTH, cursor + 1); break;
+                case 2: vga_clear(tty_row * VGA_WIDTH, (tty_row + 1) * VGA_WIDTH); break;
+            }
+            break;
+        case 'm':
+            ansi_sgr();
+            break;
+        case 's':
+            saved_row = tty_row;
+            saved_col = tty_col;
+            break;
+        case 'u':
+            tty_row = saved_row;
+            tty_col = saved_col;
+            break;
+        default:
+            // Unknown sequences are dropped
+            break;
+    }
+}
+
+static void vga_putglyph(uint8_t glyph)
+{
+    vga_printchar(glyph, tty_row, tty_col, tty_color);
+    tty_col++;
+    if (tty_col >= VGA_WIDTH)
+    {
+        vga_newline();
+    }
+}
+
+// Feed one byte of UTF-8, returns true once it was consumed as part of a multibyte sequence
+static bool utf8_decode(uint8_t byte)
+{
+    if (utf8_remaining > 0)
+    {
+        if ((byte & 0xC0) == 0x80)
         {
-            vga_printchar(data[i], tty_row, tty_col, tty_color);
-            tty_col++;
-            if (tty_col >= VGA_WIDTH)
+            utf8_codepoint = utf8_codepoint << 6 | (byte & 0x3F);
+            if (--utf8_remaining == 0)
+            {
+                // Reject overlong encodings, surrogates and anything past U+10FFFF
+                bool valid = utf8_codepoint >= utf8_min && utf8_codepoint <= 0x10FFFF
+                    && (utf8_codepoint < 0xD800 || utf8_codepoint > 0xDFFF);
+                vga_putglyph(valid ? cp437_from_unicode(utf8_codepoint) : CP437_REPLACEMENT);
+            }
+            return true;
+        }
+
+        // Truncated sequence, the byte that cut it short is handled on its own
+        utf8_remaining = 0;
+        vga_putglyph(CP437_REPLACEMENT);
+    }
+
+    if (byte < 0x80)
+    {
+        return false;
+    }
+
+    if ((byte & 0xE0) == 0xC0)
+    {
+        utf8_codepoint = byte & 0x1F;
+        utf8_remaining = 1;
+        utf8_min = 0x80;
+    }
+    else if ((byte & 0xF0) == 0xE0)
+    {
+        utf8_codepoint = byte & 0x0F;
+        utf8_remaining = 2;
+        utf8_min = 0x800;
+    }
+    else if ((byte & 0xF8) == 0xF0)
+    {
+        utf8_codepoint = byte & 0x07;
+        utf8_remaining = 3;
+        utf8_min = 0x10000;
+    }
+    else
+    {
+        vga_putglyph(CP437_REPLACEMENT);
+    }
+    return true;
+}
+
+static void tty_putchar(char c)
+{
+    switch (ansi_state)
+    {
+        case ANSI_NORMAL:
+            break;
+        case ANSI_ESCAPE:
+            if (c == '[')
+            {
+                memset(ansi_params, 0, sizeof(ansi_params));
+                ansi_index = 0;
+                ansi_private = false;
+                ansi_state = ANSI_CSI;
+            }
+            else
+            {
+                // Two character sequences aren't supported, swallow them
+                ansi_state = ANSI_NORMAL;
+            }
+            return;
+        case ANSI_CSI:
+            if (c >= '0' && c <= '9')
             {
-                tty_col = 0;
-                tty_row++;
-                if (tty_row >= VGA_HEIGHT)
+                if (ansi_index < ANSI_MAX_PARAMS && ansi_params[ansi_index] <= ANSI_MAX_PARAM_VALUE)
                 {
-                    tty_row = 0;
+                    ansi_params[ansi_index] = ansi_params[ansi_index] * 10 + (c - '0');
                 }
             }
+            else if (c == ';')
+            {
+                if (ansi_index < ANSI_MAX_PARAMS)
+                {
+                    ++ansi_index;
+                }
+            }
+            else if (c >= '<' && c <= '?')
+            {
+                ansi_private = true;
+            }
+            else if (c >= '@' && c <= '~')
+            {
+                ansi_csi_dispatch(c);
+                ansi_state = ANSI_NORMAL;
+            }
+            else if (c < ' ' || c > '/')
+            {
+                // Not a valid CSI byte, abandon the sequence
+                ansi_state = ANSI_NORMAL;
+            }
+            return;
+    }
+
+    if (utf8_decode(c))
+    {
+        return;
+    }
+
+    if (c == ANSI_ESC)
+    {
+        ansi_state = ANSI_ESCAPE;
+    }
+    else if (c == '\n')
+    {
+        vga_newline();
+    }
+    else if (c == '\r')
+    {
+        tty_col = 0;
+    }
+    else if (c == '\b')
+    {
+        // Only moves the cursor, erasing is done by writing a space over it
+        if (tty_col > 0)
+        {
+            tty_col--;
+        }
+    }
+    else if (c == '\t')
+    {
+        size_t next = (tty_col / tty_tab_width + 1) * tty_tab_width;
+        if (next >= VGA_WIDTH)
+        {
+            vga_newline();
+        }
+        else
+        {
+            tty_col = next;
+        }
+    }
+    else if (c == '\a')
+    {
+        // Output runs with interrupts off, so let a timer end the tone instead of waiting for it
+        if (timer_running())
+        {
+            speaker_on(TTY_BELL_FREQ);
+            timer_oneshot(&bell_timer, TTY_BELL_MS);
+        }
+    }
+    else
+    {
+        vga_putglyph(c);
+    }
+}
+
+void tty_write(const char *data, size_t len)
+{
+    // The periodic flush runs from the timer interrupt, keep it out while the buffer changes
+    bool irq = arch_irq_save();
+    for (size_t i = 0; i < len; ++i)
+    {
+        tty_putchar(data[i]);
+    }
+    if (autoflush)
+    {
+        tty_flush();
+    }
+    arch_irq_restore(irq);
+}
+
+void tty_flush(void)
+{
+    bool irq = arch_irq_save();
+    for (size_t row = 0; row < VGA_HEIGHT; ++row)
+    {
+        for (size_t col = dirty_first[row]; col <= dirty_last[row] && col < VGA_WIDTH; ++col)
+        {
+            size_t index = row * VGA_WIDTH + col;
+            tty_buffer[index] = back_buffer[index];
         }
+        mark_clean(row);
+    }
+    arch_irq_restore(irq);
+}
+
+void tty_set_autoflush(bool enabled)
+{
+    autoflush = enabled;
+    if (enabled)
+    {
+        tty_flush();
+    }
+}
+
+uint16_t tty_get_cell(size_t row, size_t col)
+{
+    if (row >= VGA_HEIGHT || col >= VGA_WIDTH)
+    {
+        return 0;
     }
+    return back_buffer[row * VGA_WIDTH + col];
+}
+
+size_t tty_width(void)
+{
+    return VGA_WIDTH;
+}
+
+size_t tty_height(void)
+{
+    return VGA_HEIGHT;
+}
+
+void tty_set_tab_width(size_t width)
+{
+    if (width > 0 && width < VGA_WIDTH)
+    {
+        tty_tab_width = width;
+    }
+}
+
+static int tty_dev_write(device_t *dev, uint64_t offset, const void *buf, size_t len)
+{
+    (void) dev;
+    (void) offset;
+    tty_write((const char*) buf, len);
+    return len;
 }
 
 void tty_writestring(const char *str)
@@ -66,7 +677,8 @@ void tty_writestring(const char *str)
 
 void tty_setcolor(color_t color)
 {
-    tty_color = vga_entry_color(color, BLACK);
+    tty_fg = color;
+    update_color();
 }
 
 void tty_colortest(void)
@@ -78,4 +690,4 @@ void tty_colortest(void)
     }
     tty_setcolor(DEFAULT_COLOR);
     tty_writestring("\n");
-}
\ No newline at end of file
+}
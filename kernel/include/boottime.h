@@ -0,0 +1,12 @@
+#ifndef KERNEL_BOOTTIME_H
+#define KERNEL_BOOTTIME_H
+
+#define BOOT_PHASES_MAX 24
+
+// Start timing a named boot phase, ending the previous one. name must outlive the boot.
+void boot_phase(const char *name);
+
+// End the last phase and log how long each one took
+void boot_timeline_print(void);
+
+#endif
@@ -0,0 +1,17 @@
+#ifndef KERNEL_CMDLINE_H
+#define KERNEL_CMDLINE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#define CMDLINE_MAX 256
+
+void cmdline_init(const char *cmdline);
+const char *cmdline_raw(void);
+
+// Look up "key=value", copying the value into buf. Returns false if the key isn't present
+bool cmdline_get(const char *key, char *buf, size_t len);
+// Check for a bare "flag" or a "flag=..." option
+bool cmdline_has(const char *flag);
+
+#endif
@@ -1,6 +1,46 @@
 #ifndef KERNEL_CPU_H
 #define KERNEL_CPU_H
 
+#include <stdbool.h>
+#include <stdint.h>
+
 void arch_init(void);
+void arch_enable_interrupts(void);
+void arch_disable_interrupts(void);
+// Sleep until the next interrupt arrives
+void arch_idle(void);
+// Hint that the CPU is in a busy-wait loop
+void arch_pause(void);
+// Stop the CPU for good with interrupts disabled
+__attribute__((noreturn)) void arch_halt(void);
+__attribute__((noreturn)) void arch_reboot(void);
+
+// Disable interrupts, returning whether they were enabled so it can be undone
+bool arch_irq_save(void);
+void arch_irq_restore(bool enabled);
+
+// Hardware entropy sources, return false if unavailable or exhausted
+bool arch_random_seed(uint32_t *value);
+bool arch_random(uint32_t *value);
+
+// Free running cycle counter and its calibrated frequency in Hz
+uint64_t arch_cycles(void);
+uint64_t arch_cycles_frequency(void);
+
+// Hypervisor provided clock in nanoseconds, returns false when there isn't one
+bool arch_pv_clock(uint64_t *ns);
+
+// Print the saved register state of an interrupt frame and walk its call stack.
+// A NULL frame dumps the caller instead.
+void arch_dump_registers(const void *frame);
+void arch_backtrace(const void *frame);
+
+// Report each saved register of an interrupt frame by name, and where its stack was
+typedef void (*arch_register_callback_t)(const char *name, uintptr_t value);
+void arch_frame_registers(const void *frame, arch_register_callback_t callback);
+uintptr_t arch_frame_stack(const void *frame);
+
+// Leave QEMU through its isa-debug-exit device, returns if the device isn't there
+void arch_qemu_exit(uint32_t code);
 
-#endif
\ No newline at end of file
+#endif
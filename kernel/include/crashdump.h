@@ -0,0 +1,25 @@
+#ifndef KERNEL_CRASHDUMP_H
+#define KERNEL_CRASHDUMP_H
+
+#include <stdbool.h>
+
+#include <multiboot.h>
+
+#define CRASHDUMP_STACK_BYTES 512
+
+// Enable dumps if "crashdump=ttyS<n>[,<baud>...]" was given. mbi may be NULL.
+void crashdump_init(const multiboot_info_t *mbi);
+bool crashdump_enabled(void);
+
+// Stream a line based dump of the crash to the configured serial port:
+//
+// MOLECULE-CRASH-BEGIN 1
+// message <text>
+// reg <name> <hex>
+// stack <hex address> <hex word>...
+// mmap <hex base> <hex length> <type>
+// log <seq> <microseconds> <level> <module> <message>
+// MOLECULE-CRASH-END
+void crashdump_write(const void *frame, const char *message);
+
+#endif
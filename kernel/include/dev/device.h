@@ -0,0 +1,41 @@
+#ifndef KERNEL_DEVICE_H
+#define KERNEL_DEVICE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#define DEVICE_MAX 32
+
+// Negative return values from device read/write
+#define DEVICE_EIO -1
+#define DEVICE_EINVAL -2
+#define DEVICE_ENOSPC -3
+
+typedef enum device_type_t
+{
+    DEVICE_CHAR,
+    DEVICE_BLOCK,
+} device_type_t;
+
+typedef struct device_t device_t;
+
+// read and write return the number of bytes transferred or a negative error.
+// Block devices only accept sector aligned offsets and lengths.
+struct device_t
+{
+    const char *name;
+    device_type_t type;
+    int (*read)(device_t *dev, uint64_t offset, void *buf, size_t len);
+    int (*write)(device_t *dev, uint64_t offset, const void *buf, size_t len);
+    void *data;
+};
+
+bool device_register(device_t *dev);
+size_t device_count(void);
+device_t *device_get(size_t index);
+device_t *device_find(const char *name);
+int device_read(device_t *dev, uint64_t offset, void *buf, size_t len);
+int device_write(device_t *dev, uint64_t offset, const void *buf, size_t len);
+
+#endif
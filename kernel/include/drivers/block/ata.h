@@ -0,0 +1,9 @@
+#ifndef ATA_DRIVER_H
+#define ATA_DRIVER_H
+
+#include <stddef.h>
+
+// Probe both legacy IDE channels and register every ATA disk found
+size_t ata_init(void);
+
+#endif
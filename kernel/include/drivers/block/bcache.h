@@ -0,0 +1,25 @@
+#ifndef BCACHE_DRIVER_H
+#define BCACHE_DRIVER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <drivers/block/block.h>
+
+#define BCACHE_BUFFERS 64
+#define BCACHE_SECTOR_SIZE 512
+// Most sectors written back with a single device command
+#define BCACHE_MAX_RUN 16
+
+// Write-back sector cache in front of block devices, not safe to use from interrupt context.
+// Devices with a sector size other than BCACHE_SECTOR_SIZE bypass the cache.
+bool bcache_read(block_device_t *dev, uint64_t lba, size_t count, void *buf);
+bool bcache_write(block_device_t *dev, uint64_t lba, size_t count, const void *buf);
+
+// Write back dirty sectors of a device, or of every device when dev is NULL
+bool bcache_sync(block_device_t *dev);
+// Drop a device's cached sectors without writing them back
+void bcache_invalidate(block_device_t *dev);
+
+#endif
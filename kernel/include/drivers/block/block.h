@@ -0,0 +1,29 @@
+#ifndef BLOCK_DRIVER_H
+#define BLOCK_DRIVER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#define BLOCK_MAX_DEVICES 8
+
+typedef struct block_device_t block_device_t;
+
+struct block_device_t
+{
+    const char *name;
+    size_t sector_size;
+    uint64_t sector_count;
+    bool (*read)(block_device_t *dev, uint64_t lba, size_t count, void *buf);
+    bool (*write)(block_device_t *dev, uint64_t lba, size_t count, const void *buf);
+    void *data;
+};
+
+bool block_register(block_device_t *dev);
+size_t block_count(void);
+block_device_t *block_get(size_t index);
+block_device_t *block_find(const char *name);
+bool block_read(block_device_t *dev, uint64_t lba, size_t count, void *buf);
+bool block_write(block_device_t *dev, uint64_t lba, size_t count, const void *buf);
+
+#endif
@@ -0,0 +1,13 @@
+#ifndef RAMDISK_DRIVER_H
+#define RAMDISK_DRIVER_H
+
+#include <stddef.h>
+
+#include <drivers/block/block.h>
+
+#define RAMDISK_SECTOR_SIZE 512
+
+// Expose [base, base + size) as a block device, any partial trailing sector is ignored
+block_device_t *ramdisk_create(void *base, size_t size);
+
+#endif
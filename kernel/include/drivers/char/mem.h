@@ -0,0 +1,7 @@
+#ifndef MEM_DRIVER_H
+#define MEM_DRIVER_H
+
+// Register the null, zero and full pseudo devices
+void mem_devices_init(void);
+
+#endif
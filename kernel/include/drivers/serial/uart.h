@@ -0,0 +1,49 @@
+#ifndef UART_DRIVER_H
+#define UART_DRIVER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#define COM1 0x3F8
+#define COM2 0x2F8
+#define COM3 0x3E8
+#define COM4 0x2E8
+
+#define SERIAL_PORTS 4
+#define SERIAL_DEFAULT_BAUD 38400
+
+typedef enum serial_parity_t
+{
+    SERIAL_PARITY_NONE,
+    SERIAL_PARITY_ODD,
+    SERIAL_PARITY_EVEN,
+} serial_parity_t;
+
+typedef struct serial_config_t
+{
+    uint32_t baud;
+    uint8_t data_bits;
+    serial_parity_t parity;
+    uint8_t stop_bits;
+} serial_config_t;
+
+typedef void (*serial_rx_handler_t)(int port, char c);
+
+// Probe COM1-COM4 and configure every port found at 38400 8N1
+void serial_init(void);
+bool serial_present(int port);
+bool serial_configure(int port, const serial_config_t *config);
+
+// Parse a "ttyS<n>[,<baud>[<parity>[<bits>]]]" spec, e.g. "ttyS1,115200n8"
+bool serial_parse(const char *spec, int *port, serial_config_t *config);
+
+bool serial_received(int port);
+char serial_getc(int port);
+void serial_putc(int port, char c);
+void serial_write(int port, const char *data, size_t len);
+
+// Deliver received bytes from the port's IRQ instead of polling
+bool serial_set_rx_handler(int port, serial_rx_handler_t handler);
+
+#endif
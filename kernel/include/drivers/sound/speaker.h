@@ -0,0 +1,12 @@
+#ifndef SPEAKER_DRIVER_H
+#define SPEAKER_DRIVER_H
+
+#include <stdint.h>
+
+void speaker_on(uint32_t freq);
+void speaker_off(void);
+
+// Busy-waits for the duration. Don't call with interrupts disabled, ticks and input would be lost.
+void speaker_beep(uint32_t freq, uint32_t ms);
+
+#endif
@@ -0,0 +1,28 @@
+#ifndef PIT_DRIVER_H
+#define PIT_DRIVER_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define PIT_FREQUENCY 1193182
+#define PIT_TICK_HZ 1000
+#define PIT_MAX_TICK_CALLBACKS 4
+
+typedef void (*pit_tick_callback_t)(uint64_t ticks);
+
+// Channel 0 drives the periodic tick on IRQ 0
+void pit_init(void);
+uint64_t pit_ticks(void);
+void pit_sleep(uint32_t ms);
+// Run a callback from the timer interrupt on every tick
+bool pit_register_tick(pit_tick_callback_t callback);
+
+// Channel 2 one-shot countdown, polled, usable before interrupts are enabled
+void pit_countdown_start(uint16_t count);
+bool pit_countdown_done(void);
+void pit_countdown_stop(void);
+
+// Channel 2 square wave, the output feeds the PC speaker
+void pit_square_wave(uint32_t hz);
+
+#endif
@@ -0,0 +1,13 @@
+#ifndef RTC_DRIVER_H
+#define RTC_DRIVER_H
+
+#include <stdbool.h>
+
+#include <time/time.h>
+
+// Read the CMOS real time clock, which keeps UTC. Returns false if it never settled.
+bool rtc_read(date_t *date);
+// A single sample that never waits, false if an update was in progress. Usable from interrupt context.
+bool rtc_try_read(date_t *date);
+
+#endif
@@ -0,0 +1,14 @@
+#ifndef CP437_DRIVER_H
+#define CP437_DRIVER_H
+
+#include <stdint.h>
+
+// Drawn for code points the VGA font has no glyph for (a small filled square)
+#define CP437_REPLACEMENT 0xFE
+
+// Glyph index in the VGA code page 437 font for a Unicode code point
+uint8_t cp437_from_unicode(uint32_t codepoint);
+// Unicode code point of what the VGA font draws for a glyph index
+uint32_t cp437_to_unicode(uint8_t glyph);
+
+#endif
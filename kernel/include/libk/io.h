@@ -1,8 +1,20 @@
 #ifndef LIBK_IO_H
 #define LIBK_IO_H
 
+#include <stdarg.h>
 #include <stddef.h>
 
 void kprintf(const char *format, ...);
+void kvprintf(const char *format, va_list parameters);
+
+// Format into buf, truncating to size - 1 characters. Returns the untruncated length.
+int ksnprintf(char *buf, size_t size, const char *format, ...);
+int kvsnprintf(char *buf, size_t size, const char *format, va_list parameters);
+
+void kwrite(const char *str, size_t len);
+
+// Also send kernel output to a serial port, -1 to disable
+void kprintf_mirror_serial(int port);
+int kprintf_mirror_port(void);
 
 #endif
\ No newline at end of file
@@ -8,7 +8,10 @@ void* memcpy(void*  __restrict, const void* __restrict, size_t);
 void* memmove(void*, const void*, size_t);
 void* memset(void*, int, size_t);
 size_t strlen(const char*);
+int strcmp(const char*, const char*);
+int strncmp(const char*, const char*, size_t);
 
 char *itoa(int num, char *str, int base);
+char *utoa(unsigned int num, char *str, int base);
 
 #endif
\ No newline at end of file
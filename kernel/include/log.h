@@ -0,0 +1,62 @@
+#ifndef KERNEL_LOG_H
+#define KERNEL_LOG_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define LOG_RECORDS 64
+#define LOG_MODULE_MAX 16
+#define LOG_MESSAGE_MAX 160
+#define LOG_FILTERS 8
+
+// Levels are ordered so that a message is printed when its level is at or below the filter
+typedef enum log_level_t
+{
+    LOG_OFF,
+    LOG_ERROR,
+    LOG_WARN,
+    LOG_INFO,
+    LOG_DEBUG,
+    LOG_TRACE,
+} log_level_t;
+
+typedef struct log_record_t
+{
+    uint64_t seq;
+    uint64_t timestamp_ns;
+    log_level_t level;
+    char module[LOG_MODULE_MAX];
+    char message[LOG_MESSAGE_MAX];
+} log_record_t;
+
+// Record a message in the log ring and print it to the console, safe from interrupt context
+void klog(log_level_t level, const char *module, const char *format, ...);
+
+const char *log_level_name(log_level_t level);
+bool log_parse_level(const char *name, log_level_t *level);
+
+// Highest level printed to the console, records are kept in the ring regardless
+void log_set_level(log_level_t level);
+log_level_t log_get_level(void);
+
+// Override the console level for one module, false if the filter table is full
+bool log_set_module_level(const char *module, log_level_t level);
+void log_clear_module_level(const char *module);
+
+// Apply a filter spec such as "debug,ata:off,time:trace". A bare level sets the global level.
+bool log_configure(const char *spec);
+
+// Apply the "loglevel=" and "log=" command line options
+void log_init(void);
+
+// Sequence numbers of the oldest record still in the ring and of the next one to be written
+uint64_t log_first_seq(void);
+uint64_t log_next_seq(void);
+
+// Copy out the record with the given sequence number, false if it was overwritten or not written yet
+bool log_get(uint64_t seq, log_record_t *record);
+
+// Print every record still in the ring
+void log_dump(void);
+
+#endif
@@ -0,0 +1,29 @@
+#ifndef KERNEL_MODULE_H
+#define KERNEL_MODULE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#include <multiboot.h>
+
+#define MODULE_MAX 16
+#define MODULE_NAME_MAX 64
+
+// A file loaded next to the kernel by the boot loader
+typedef struct boot_module_t
+{
+    char name[MODULE_NAME_MAX];
+    const char *args;
+    const uint8_t *data;
+    size_t size;
+} boot_module_t;
+
+// Collect the modules from the Multiboot info, mbi may be NULL
+void module_init(const multiboot_info_t *mbi);
+size_t module_count(void);
+const boot_module_t *module_get(size_t index);
+
+// Find a module by its path ("/boot/initrd.tar") or just the file name ("initrd.tar")
+const boot_module_t *module_find(const char *name);
+
+#endif
@@ -0,0 +1,11 @@
+#ifndef KERNEL_PANIC_H
+#define KERNEL_PANIC_H
+
+// Show the panic screen and halt, also mirrored to COM1 if no serial console is set up.
+// A crash dump follows on the crashdump= port if one was given.
+__attribute__((noreturn)) void panic(const char *format, ...);
+
+// Same as panic, dumping the registers saved in an interrupt frame
+__attribute__((noreturn)) void panic_frame(const void *frame, const char *format, ...);
+
+#endif
@@ -0,0 +1,12 @@
+#ifndef KERNEL_RAND_H
+#define KERNEL_RAND_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+void rand_init(void);
+void rand_mix(const void *data, size_t len);
+void rand_fill(void *buf, size_t len);
+uint32_t rand_u32(void);
+
+#endif
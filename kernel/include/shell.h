@@ -0,0 +1,13 @@
+#ifndef KERNEL_SHELL_H
+#define KERNEL_SHELL_H
+
+#include <multiboot.h>
+#include <tty/ldisc.h>
+
+#define SHELL_LINE_MAX 128
+#define SHELL_MAX_ARGS 8
+
+// Read and run commands from a line discipline forever. mbi may be NULL if the loader didn't provide it.
+__attribute__((noreturn)) void shell_run(ldisc_t *ldisc, int serial_port, const multiboot_info_t *mbi);
+
+#endif
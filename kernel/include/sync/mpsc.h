@@ -0,0 +1,35 @@
+#ifndef KERNEL_MPSC_H
+#define KERNEL_MPSC_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct mpsc_slot_t
+{
+    uint32_t seq;
+    uintptr_t value;
+} mpsc_slot_t;
+
+// Bounded lock-free queue. Any number of producers, including interrupt handlers, may push;
+// a single consumer pops.
+typedef struct mpsc_queue_t
+{
+    mpsc_slot_t *slots;
+    uint32_t mask;
+    uint32_t head;
+    uint32_t tail;
+} mpsc_queue_t;
+
+// capacity must be a power of two, slots must hold that many entries
+void mpsc_init(mpsc_queue_t *queue, mpsc_slot_t *slots, size_t capacity);
+
+// Returns false if the queue is full, never blocks
+bool mpsc_push(mpsc_queue_t *queue, uintptr_t value);
+
+// Returns false if the queue is empty
+bool mpsc_pop(mpsc_queue_t *queue, uintptr_t *value);
+// Wait for a value, interrupts must be enabled for it to ever arrive
+uintptr_t mpsc_pop_wait(mpsc_queue_t *queue);
+
+#endif
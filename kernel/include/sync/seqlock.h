@@ -0,0 +1,47 @@
+#ifndef KERNEL_SEQLOCK_H
+#define KERNEL_SEQLOCK_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <cpu.h>
+
+// Sequence lock for data with a single writer and readers that retry instead of blocking it.
+// The count is odd while a write is in progress.
+typedef struct seqlock_t
+{
+    uint32_t seq;
+} seqlock_t;
+
+#define SEQLOCK_INIT { 0 }
+
+// Writers must not be interrupted by another writer of the same lock
+static inline void seqlock_write_begin(seqlock_t *lock)
+{
+    __atomic_store_n(&lock->seq, lock->seq + 1, __ATOMIC_RELAXED);
+    __atomic_thread_fence(__ATOMIC_RELEASE);
+}
+
+static inline void seqlock_write_end(seqlock_t *lock)
+{
+    __atomic_store_n(&lock->seq, lock->seq + 1, __ATOMIC_RELEASE);
+}
+
+static inline uint32_t seqlock_read_begin(const seqlock_t *lock)
+{
+    uint32_t seq;
+    while ((seq = __atomic_load_n(&lock->seq, __ATOMIC_ACQUIRE)) & 1)
+    {
+        arch_pause();
+    }
+    return seq;
+}
+
+// True if a write happened since seqlock_read_begin and what was read must be thrown away
+static inline bool seqlock_read_retry(const seqlock_t *lock, uint32_t seq)
+{
+    __atomic_thread_fence(__ATOMIC_ACQUIRE);
+    return __atomic_load_n(&lock->seq, __ATOMIC_RELAXED) != seq;
+}
+
+#endif
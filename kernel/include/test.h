@@ -0,0 +1,42 @@
+#ifndef KERNEL_TEST_H
+#define KERNEL_TEST_H
+
+#ifdef KERNEL_TEST
+
+#include <stdbool.h>
+
+// QEMU exits with (code << 1) | 1, so 33 for success and 35 for failure
+#define TEST_EXIT_SUCCESS 0x10
+#define TEST_EXIT_FAILURE 0x11
+
+typedef struct test_case_t
+{
+    const char *name;
+    void (*func)(void);
+} test_case_t;
+
+// Define a test that runs at boot in KERNEL_TEST builds
+#define TEST_CASE(fn) \
+    static void fn(void); \
+    __attribute__((section(".test_cases"), used)) static const test_case_t fn##_case = { #fn, fn }; \
+    static void fn(void)
+
+// Fail the current test and return from it
+#define TEST_ASSERT(cond) \
+    do \
+    { \
+        if (!(cond)) \
+        { \
+            test_fail(__FILE__, __LINE__, #cond); \
+            return; \
+        } \
+    } while (0)
+
+void test_fail(const char *file, int line, const char *cond);
+
+// Run every registered test, report over COM1 and exit QEMU with the result
+__attribute__((noreturn)) void test_run_all(void);
+
+#endif
+
+#endif
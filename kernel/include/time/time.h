@@ -0,0 +1,44 @@
+#ifndef KERNEL_TIME_H
+#define KERNEL_TIME_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define NS_PER_SEC 1000000000ULL
+#define NS_PER_MS 1000000ULL
+#define NS_PER_US 1000ULL
+
+// Broken down calendar time
+typedef struct date_t
+{
+    int year;
+    int month; // 1-12
+    int day; // 1-31
+    int hour;
+    int minute;
+    int second;
+} date_t;
+
+void time_init(void);
+const char *time_source(void);
+
+// Monotonic nanoseconds since boot
+uint64_t time_now_ns(void);
+// Nanoseconds since the Unix epoch, false if the wall clock is unknown
+bool time_wall_ns(uint64_t *ns);
+
+// Offset from UTC in minutes, set with tz= on the command line
+int time_tz_offset(void);
+// Wall clock time broken down in the local timezone
+bool time_local_date(date_t *date);
+
+// Busy-wait for short device delays, usable with interrupts disabled and before time_init
+void ndelay(uint32_t ns);
+void udelay(uint32_t us);
+void mdelay(uint32_t ms);
+
+// Conversions between calendar dates and seconds since the Unix epoch, valid from 1970 on
+uint64_t time_from_date(const date_t *date);
+void time_to_date(uint64_t seconds, date_t *date);
+
+#endif
@@ -0,0 +1,36 @@
+#ifndef KERNEL_TIMER_H
+#define KERNEL_TIMER_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+typedef struct timer_t timer_t;
+
+// Runs from the timer interrupt with interrupts disabled, keep it short
+typedef void (*timer_callback_t)(timer_t *timer, void *data);
+
+// Owned by the caller and must stay alive while armed
+struct timer_t
+{
+    timer_t *next;
+    timer_t **pprev;
+    uint64_t expires; // In PIT ticks
+    uint32_t period; // 0 for one-shot timers
+    timer_callback_t callback;
+    void *data;
+};
+
+// Hook the timer wheel up to the PIT tick
+bool timer_init(void);
+// False until timer_init has succeeded, armed timers don't fire before that
+bool timer_running(void);
+
+void timer_setup(timer_t *timer, timer_callback_t callback, void *data);
+
+// Arm a timer, or re-arm one that is already pending. Also safe from a timer callback.
+void timer_oneshot(timer_t *timer, uint32_t ms);
+void timer_periodic(timer_t *timer, uint32_t ms);
+void timer_cancel(timer_t *timer);
+bool timer_pending(const timer_t *timer);
+
+#endif
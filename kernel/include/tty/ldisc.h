@@ -0,0 +1,45 @@
+#ifndef KERNEL_LDISC_H
+#define KERNEL_LDISC_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#define LDISC_BUFFER_SIZE 256
+
+#define LDISC_CTRL(c) ((c) & 0x1F)
+
+typedef void (*ldisc_output_t)(const char *data, size_t len);
+
+// Line discipline sitting between an input source and an output sink
+typedef struct ldisc_t
+{
+    ldisc_output_t output;
+    bool canonical;
+    bool echo;
+
+    // Line currently being edited, only used in canonical mode
+    char line[LDISC_BUFFER_SIZE];
+    size_t line_len;
+
+    // Input ready to be read
+    char ready[LDISC_BUFFER_SIZE];
+    size_t ready_head;
+    size_t ready_tail;
+    bool eof;
+} ldisc_t;
+
+void ldisc_init(ldisc_t *ldisc, ldisc_output_t output);
+void ldisc_set_mode(ldisc_t *ldisc, bool canonical, bool echo);
+
+// Feed a received character, safe to call from interrupt context
+void ldisc_receive(ldisc_t *ldisc, char c);
+
+// Block until a full line (canonical) or any input (raw) is available.
+// Returns the number of bytes read, 0 on end of file.
+size_t ldisc_read(ldisc_t *ldisc, char *buf, size_t len);
+
+// Console line discipline fed from a serial port and echoing through kwrite
+ldisc_t *ldisc_console(void);
+bool ldisc_console_init(int serial_port);
+
+#endif
@@ -0,0 +1,13 @@
+#ifndef KERNEL_SCREENDUMP_H
+#define KERNEL_SCREENDUMP_H
+
+// Write the console contents to a serial port as UTF-8 text followed by a hex dump of the attributes:
+//
+// -----BEGIN SCREEN 80x25-----
+// one line per row, trailing blanks trimmed
+// -----ATTRIBUTES-----
+// one line per row, two hex digits per cell
+// -----END SCREEN-----
+void screendump(int serial_port);
+
+#endif
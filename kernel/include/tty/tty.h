@@ -1,7 +1,9 @@
 #ifndef KERNEL_TTY_H
 #define KERNEL_TTY_H
 
+#include <stdbool.h>
 #include <stddef.h>
+#include <stdint.h>
 
 typedef enum color_t
 {
@@ -27,6 +29,16 @@ typedef enum color_t
 
 void tty_init(void);
 void tty_write(const char *data, size_t len);
+void tty_flush(void);
+// When disabled, output only reaches the screen on tty_flush
+void tty_set_autoflush(bool enabled);
+void tty_set_tab_width(size_t width);
+
+// Screen contents including output not yet flushed, the glyph in the low byte and the VGA attribute in the high byte
+uint16_t tty_get_cell(size_t row, size_t col);
+size_t tty_width(void);
+size_t tty_height(void);
+
 void tty_writestring(const char *str);
 void tty_setcolor(color_t color);
 void tty_colortest(void);
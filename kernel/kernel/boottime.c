@@ -0,0 +1,50 @@
+#include <stddef.h>
+#include <stdint.h>
+
+#include <boottime.h>
+#include <cpu.h>
+#include <log.h>
+
+typedef struct boot_phase_t
+{
+    const char *name;
+    uint64_t start;
+} boot_phase_t;
+
+// Cycle counts are stored raw, the counter frequency isn't known until it has been calibrated
+static boot_phase_t phases[BOOT_PHASES_MAX];
+static size_t num_phases;
+
+void boot_phase(const char *name)
+{
+    if (num_phases < BOOT_PHASES_MAX)
+    {
+        phases[num_phases].name = name;
+        phases[num_phases].start = arch_cycles();
+        ++num_phases;
+    }
+}
+
+static uint32_t cycles_to_us(uint64_t cycles, uint64_t hz)
+{
+    return hz ? cycles * 1000000 / hz : 0;
+}
+
+void boot_timeline_print(void)
+{
+    if (num_phases == 0)
+    {
+        return;
+    }
+
+    uint64_t end = arch_cycles();
+    uint64_t hz = arch_cycles_frequency();
+    for (size_t i = 0; i < num_phases; ++i)
+    {
+        uint64_t next = i + 1 < num_phases ? phases[i + 1].start : end;
+        uint32_t us = cycles_to_us(next - phases[i].start, hz);
+        klog(LOG_INFO, "boot", "%s: %u.%03u ms", phases[i].name, us / 1000, us % 1000);
+    }
+    uint32_t total = cycles_to_us(end - phases[0].start, hz);
+    klog(LOG_INFO, "boot", "total: %u.%03u ms", total / 1000, total % 1000);
+}
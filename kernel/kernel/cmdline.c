@@ -0,0 +1,77 @@
+#include <stdbool.h>
+#include <stddef.h>
+
+#include <cmdline.h>
+#include <libk/string.h>
+
+static char cmdline[CMDLINE_MAX];
+
+void cmdline_init(const char *str)
+{
+    size_t len = 0;
+    if (str)
+    {
+        while (str[len] && len < CMDLINE_MAX - 1)
+        {
+            cmdline[len] = str[len];
+            ++len;
+        }
+    }
+    cmdline[len] = '\0';
+}
+
+const char *cmdline_raw(void)
+{
+    return cmdline;
+}
+
+// Find the option named key, returning a pointer just past the name
+static const char *cmdline_find(const char *key)
+{
+    size_t key_len = strlen(key);
+    const char *option = cmdline;
+    while (*option)
+    {
+        while (*option == ' ')
+        {
+            ++option;
+        }
+
+        const char *end = option;
+        while (*end && *end != ' ')
+        {
+            ++end;
+        }
+
+        if (strncmp(option, key, key_len) == 0 && (option[key_len] == '=' || option + key_len == end))
+        {
+            return option + key_len;
+        }
+        option = end;
+    }
+    return NULL;
+}
+
+bool cmdline_get(const char *key, char *buf, size_t len)
+{
+    const char *value = cmdline_find(key);
+    if (!value || *value != '=' || len == 0)
+    {
+        return false;
+    }
+    ++value;
+
+    size_t i = 0;
+    while (value[i] && value[i] != ' ' && i < len - 1)
+    {
+        buf[i] = value[i];
+        ++i;
+    }
+    buf[i] = '\0';
+    return true;
+}
+
+bool cmdline_has(const char *flag)
+{
+    return cmdline_find(flag) != NULL;
+}
@@ -0,0 +1,133 @@
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <cmdline.h>
+#include <cpu.h>
+#include <crashdump.h>
+#ifdef GDBSTUB
+#include <debug/gdbstub.h>
+#endif
+#include <drivers/serial/uart.h>
+#include <libk/io.h>
+#include <libk/string.h>
+#include <log.h>
+#include <time/time.h>
+
+#define CRASHDUMP_VERSION 1
+#define CRASHDUMP_LINE_MAX 256
+#define STACK_WORDS_PER_LINE 8
+
+static int dump_port = -1;
+static const multiboot_info_t *boot_info;
+
+void crashdump_init(const multiboot_info_t *mbi)
+{
+    char spec[32];
+    int port;
+    serial_config_t config;
+
+    boot_info = mbi;
+    if (!cmdline_get("crashdump", spec, sizeof(spec)))
+    {
+        return;
+    }
+
+    if (!serial_parse(spec, &port, &config))
+    {
+        klog(LOG_WARN, "crashdump", "Invalid or missing serial port %s", spec);
+        return;
+    }
+#ifdef GDBSTUB
+    if (port == GDBSTUB_SERIAL_PORT)
+    {
+        klog(LOG_WARN, "crashdump", "Serial port %s is reserved for the GDB stub", spec);
+        return;
+    }
+#endif
+    if (!serial_configure(port, &config))
+    {
+        klog(LOG_WARN, "crashdump", "Invalid or missing serial port %s", spec);
+        return;
+    }
+    dump_port = port;
+}
+
+bool crashdump_enabled(void)
+{
+    return dump_port >= 0;
+}
+
+static void dump_line(const char *format, ...)
+{
+    char line[CRASHDUMP_LINE_MAX];
+    va_list parameters;
+    va_start(parameters, format);
+    kvsnprintf(line, sizeof(line), format, parameters);
+    va_end(parameters);
+
+    serial_write(dump_port, line, strlen(line));
+    serial_write(dump_port, "\r\n", 2);
+}
+
+static void dump_register(const char *name, uintptr_t value)
+{
+    dump_line("reg %s %08x", name, value);
+}
+
+static void dump_stack(uintptr_t sp)
+{
+    const uint32_t *words = (const uint32_t*) (sp & ~3u);
+    for (size_t i = 0; i < CRASHDUMP_STACK_BYTES / 4; i += STACK_WORDS_PER_LINE)
+    {
+        dump_line("stack %08x %08x %08x %08x %08x %08x %08x %08x %08x", (uintptr_t) &words[i],
+            words[i], words[i + 1], words[i + 2], words[i + 3], words[i + 4], words[i + 5], words[i + 6], words[i + 7]);
+    }
+}
+
+static void dump_mmap(void)
+{
+    if (!boot_info || !(boot_info->flags & MULTIBOOT_INFO_MEM_MAP))
+    {
+        return;
+    }
+
+    uintptr_t addr = boot_info->mmap_addr;
+    uintptr_t end = addr + boot_info->mmap_length;
+    while (addr < end)
+    {
+        const multiboot_mmap_entry_t *entry = (const multiboot_mmap_entry_t*) addr;
+        dump_line("mmap %08x %08x %u", (uint32_t) entry->addr, (uint32_t) entry->len, entry->type);
+        addr += entry->size + sizeof(entry->size);
+    }
+}
+
+static void dump_log(void)
+{
+    log_record_t record;
+    for (uint64_t seq = log_first_seq(); seq < log_next_seq(); ++seq)
+    {
+        if (log_get(seq, &record))
+        {
+            dump_line("log %u %u %s %s %s", (unsigned int) record.seq, (unsigned int) (record.timestamp_ns / NS_PER_US),
+                log_level_name(record.level), record.module, record.message);
+        }
+    }
+}
+
+void crashdump_write(const void *frame, const char *message)
+{
+    if (dump_port < 0)
+    {
+        return;
+    }
+
+    dump_line("MOLECULE-CRASH-BEGIN %u", CRASHDUMP_VERSION);
+    dump_line("message %s", message);
+    arch_frame_registers(frame, dump_register);
+    dump_stack(arch_frame_stack(frame));
+    dump_mmap();
+    dump_log();
+    dump_line("MOLECULE-CRASH-END");
+}
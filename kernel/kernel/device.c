@@ -0,0 +1,60 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <dev/device.h>
+#include <libk/string.h>
+
+static device_t *devices[DEVICE_MAX];
+static size_t num_devices;
+
+bool device_register(device_t *dev)
+{
+    if (num_devices >= DEVICE_MAX || device_find(dev->name))
+    {
+        return false;
+    }
+
+    devices[num_devices++] = dev;
+    return true;
+}
+
+size_t device_count(void)
+{
+    return num_devices;
+}
+
+device_t *device_get(size_t index)
+{
+    return index < num_devices ? devices[index] : NULL;
+}
+
+device_t *device_find(const char *name)
+{
+    for (size_t i = 0; i < num_devices; ++i)
+    {
+        if (strcmp(devices[i]->name, name) == 0)
+        {
+            return devices[i];
+        }
+    }
+    return NULL;
+}
+
+int device_read(device_t *dev, uint64_t offset, void *buf, size_t len)
+{
+    if (!dev->read)
+    {
+        return DEVICE_EINVAL;
+    }
+    return dev->read(dev, offset, buf, len);
+}
+
+int device_write(device_t *dev, uint64_t offset, const void *buf, size_t len)
+{
+    if (!dev->write)
+    {
+        return DEVICE_EINVAL;
+    }
+    return dev->write(dev, offset, buf, len);
+}
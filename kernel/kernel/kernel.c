@@ -1,13 +1,113 @@
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <boottime.h>
+#include <cmdline.h>
 #include <cpu.h>
+#include <crashdump.h>
+#include <drivers/block/ata.h>
+#include <drivers/block/ramdisk.h>
+#include <drivers/char/mem.h>
+#include <drivers/serial/uart.h>
+#include <drivers/timer/pit.h>
+#ifdef GDBSTUB
+#include <debug/gdbstub.h>
+#endif
+#include <tty/ldisc.h>
 #include <tty/tty.h>
 #include <libk/io.h>
+#include <log.h>
+#include <module.h>
+#include <multiboot.h>
+#include <rand.h>
+#include <shell.h>
+#ifdef KERNEL_TEST
+#include <test.h>
+#endif
+#include <time/time.h>
+#include <time/timer.h>
 
 #define KERNEL_NAME "Molecule"
 #define KERNEL_VER "0.0.1 - Genesis"
 
-void kernel_main(void)
+#define CONSOLE_FLUSH_MS 20
+
+static timer_t console_flush_timer;
+
+static void console_flush(timer_t *timer, void *data)
+{
+    (void) timer;
+    (void) data;
+    tty_flush();
+}
+
+// Returns the serial port used as console, or -1 if there is none
+static int setup_serial_console(void)
 {
+    char spec[32];
+    int port;
+    serial_config_t config;
+
+    if (!cmdline_get("console", spec, sizeof(spec)))
+    {
+        return -1;
+    }
+
+    if (!serial_parse(spec, &port, &config))
+    {
+        klog(LOG_WARN, "console", "Invalid or missing serial console %s", spec);
+        return -1;
+    }
+#ifdef GDBSTUB
+    if (port == GDBSTUB_SERIAL_PORT)
+    {
+        klog(LOG_WARN, "console", "Serial console %s is reserved for the GDB stub", spec);
+        return -1;
+    }
+#endif
+    if (!serial_configure(port, &config))
+    {
+        klog(LOG_WARN, "console", "Invalid or missing serial console %s", spec);
+        return -1;
+    }
+    kprintf_mirror_serial(port);
+    return port;
+}
+
+// Give every boot module a RAM disk, so images can be passed in with GRUB's module command
+static void setup_ramdisks(void)
+{
+    for (size_t i = 0; i < module_count(); ++i)
+    {
+        const boot_module_t *module = module_get(i);
+        // Modules live in plain RAM, writes only change this boot's copy
+        block_device_t *dev = ramdisk_create((void*) module->data, module->size);
+        if (dev)
+        {
+            klog(LOG_INFO, "ramdisk", "%s: %s, %u KiB", dev->name, module->name, (unsigned int) (dev->sector_count / 2));
+        }
+        else
+        {
+            klog(LOG_WARN, "ramdisk", "No RAM disk for module %s", module->name);
+        }
+    }
+}
+
+void kernel_main(uint32_t magic, multiboot_info_t *mbi)
+{
+    boot_phase("cmdline");
+    if (magic == MULTIBOOT_BOOTLOADER_MAGIC && (mbi->flags & MULTIBOOT_INFO_CMDLINE))
+    {
+        cmdline_init((const char*) mbi->cmdline);
+    }
+
+    boot_phase("console");
     tty_init();
+    boot_phase("serial");
+    serial_init();
+    int console_port = setup_serial_console();
+    log_init();
+    crashdump_init(magic == MULTIBOOT_BOOTLOADER_MAGIC ? mbi : NULL);
     tty_setcolor(WHITE);
     kprintf("[ %s %s ]\n", KERNEL_NAME, KERNEL_VER);
     tty_setcolor(DEFAULT_COLOR);
@@ -15,9 +115,58 @@ void kernel_main(void)
     tty_colortest();
 
     arch_init();
+    boot_phase("pit");
+    pit_init();
+    if (timer_init())
+    {
+        timer_setup(&console_flush_timer, console_flush, NULL);
+        timer_periodic(&console_flush_timer, CONSOLE_FLUSH_MS);
+        tty_set_autoflush(false);
+    }
+    arch_enable_interrupts();
+    boot_phase("time");
+    time_init();
+    boot_phase("rand");
+    rand_init();
+    klog(LOG_INFO, "time", "CPU clock: %d MHz, timekeeping via %s", (int) (arch_cycles_frequency() / 1000000), time_source());
+#ifdef GDBSTUB
+    boot_phase("gdbstub");
+    gdbstub_init();
+    if (gdbstub_enabled())
+    {
+        kprintf("Waiting for GDB on COM1...\n");
+        gdbstub_breakpoint();
+    }
+#endif
+    boot_phase("modules");
+    module_init(magic == MULTIBOOT_BOOTLOADER_MAGIC ? mbi : NULL);
+    setup_ramdisks();
+    boot_phase("devices");
+    mem_devices_init();
+    boot_phase("ata");
+    ata_init();
+    boot_timeline_print();
+#ifndef GDBSTUB
+    // The stub owns the port's input when it is built in
+    bool shell = console_port >= 0 && ldisc_console_init(console_port);
+#else
+    bool shell = false;
+#endif
+#ifdef KERNEL_TEST
+    test_run_all();
+#endif
+
     kprintf("Welcome to ");
     tty_setcolor(LIGHT_CYAN);
     kprintf("Molecule");
     tty_setcolor(DEFAULT_COLOR);
     kprintf("!\n");
+
+    if (shell)
+    {
+        shell_run(ldisc_console(), console_port, magic == MULTIBOOT_BOOTLOADER_MAGIC ? mbi : NULL);
+    }
+
+    // Interrupts go off once we return to the boot stub, nothing would flush after that
+    tty_flush();
 }
\ No newline at end of file
@@ -0,0 +1,186 @@
+#include <stdbool.h>
+#include <stddef.h>
+
+#include <cpu.h>
+#include <drivers/serial/uart.h>
+#include <libk/io.h>
+#include <tty/ldisc.h>
+
+#define LDISC_INTR LDISC_CTRL('C')
+#define LDISC_EOF LDISC_CTRL('D')
+#define LDISC_KILL LDISC_CTRL('U')
+#define LDISC_BACKSPACE 0x08
+#define LDISC_DELETE 0x7F
+
+static ldisc_t console;
+
+static size_t ready_count(ldisc_t *ldisc)
+{
+    return (ldisc->ready_tail - ldisc->ready_head + LDISC_BUFFER_SIZE) % LDISC_BUFFER_SIZE;
+}
+
+// One slot is kept free to tell a full ring from an empty one
+static void ready_push(ldisc_t *ldisc, char c)
+{
+    if (ready_count(ldisc) == LDISC_BUFFER_SIZE - 1)
+    {
+        return;
+    }
+    ldisc->ready[ldisc->ready_tail] = c;
+    ldisc->ready_tail = (ldisc->ready_tail + 1) % LDISC_BUFFER_SIZE;
+}
+
+static void echo(ldisc_t *ldisc, const char *str, size_t len)
+{
+    if (ldisc->echo && ldisc->output)
+    {
+        ldisc->output(str, len);
+    }
+}
+
+static void commit_line(ldisc_t *ldisc)
+{
+    for (size_t i = 0; i < ldisc->line_len; ++i)
+    {
+        ready_push(ldisc, ldisc->line[i]);
+    }
+    ldisc->line_len = 0;
+}
+
+static void erase_char(ldisc_t *ldisc)
+{
+    if (ldisc->line_len > 0)
+    {
+        --ldisc->line_len;
+        echo(ldisc, "\b \b", 3);
+    }
+}
+
+void ldisc_init(ldisc_t *ldisc, ldisc_output_t output)
+{
+    ldisc->output = output;
+    ldisc->canonical = true;
+    ldisc->echo = true;
+    ldisc->line_len = 0;
+    ldisc->ready_head = 0;
+    ldisc->ready_tail = 0;
+    ldisc->eof = false;
+}
+
+void ldisc_set_mode(ldisc_t *ldisc, bool canonical, bool echo)
+{
+    bool irq = arch_irq_save();
+    // Anything half edited becomes readable when leaving canonical mode
+    if (ldisc->canonical && !canonical)
+    {
+        commit_line(ldisc);
+    }
+    ldisc->canonical = canonical;
+    ldisc->echo = echo;
+    arch_irq_restore(irq);
+}
+
+void ldisc_receive(ldisc_t *ldisc, char c)
+{
+    if (c == '\r')
+    {
+        c = '\n';
+    }
+
+    if (!ldisc->canonical)
+    {
+        ready_push(ldisc, c);
+        echo(ldisc, &c, 1);
+        return;
+    }
+
+    switch (c)
+    {
+        case LDISC_INTR:
+            ldisc->line_len = 0;
+            echo(ldisc, "^C\n", 3);
+            break;
+        case LDISC_EOF:
+            // ^D on an empty line is end of file, otherwise it hands over the line without a newline
+            if (ldisc->line_len == 0)
+            {
+                ldisc->eof = true;
+            }
+            commit_line(ldisc);
+            break;
+        case LDISC_KILL:
+            while (ldisc->line_len > 0)
+            {
+                erase_char(ldisc);
+            }
+            break;
+        case LDISC_BACKSPACE:
+        case LDISC_DELETE:
+            erase_char(ldisc);
+            break;
+        case '\n':
+            ldisc->line[ldisc->line_len++] = '\n';
+            echo(ldisc, "\n", 1);
+            commit_line(ldisc);
+            break;
+        default:
+            // Leave room for the terminating newline
+            if (ldisc->line_len < LDISC_BUFFER_SIZE - 1)
+            {
+                ldisc->line[ldisc->line_len++] = c;
+                echo(ldisc, &c, 1);
+            }
+            break;
+    }
+}
+
+size_t ldisc_read(ldisc_t *ldisc, char *buf, size_t len)
+{
+    while (true)
+    {
+        bool irq = arch_irq_save();
+        if (ready_count(ldisc) > 0)
+        {
+            size_t count = 0;
+            while (count < len && ready_count(ldisc) > 0)
+            {
+                char c = ldisc->ready[ldisc->ready_head];
+                ldisc->ready_head = (ldisc->ready_head + 1) % LDISC_BUFFER_SIZE;
+                buf[count++] = c;
+                if (ldisc->canonical && c == '\n')
+                {
+                    break;
+                }
+            }
+            arch_irq_restore(irq);
+            return count;
+        }
+
+        if (ldisc->eof)
+        {
+            ldisc->eof = false;
+            arch_irq_restore(irq);
+            return 0;
+        }
+
+        arch_irq_restore(irq);
+        arch_idle();
+    }
+}
+
+static void console_input(int port, char c)
+{
+    (void) port;
+    ldisc_receive(&console, c);
+}
+
+ldisc_t *ldisc_console(void)
+{
+    return &console;
+}
+
+bool ldisc_console_init(int serial_port)
+{
+    ldisc_init(&console, kwrite);
+    return serial_set_rx_handler(serial_port, console_input);
+}
@@ -0,0 +1,219 @@
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <cmdline.h>
+#include <cpu.h>
+#include <libk/io.h>
+#include <libk/string.h>
+#include <log.h>
+#include <time/time.h>
+
+typedef struct log_filter_t
+{
+    char module[LOG_MODULE_MAX];
+    log_level_t level;
+} log_filter_t;
+
+static log_record_t records[LOG_RECORDS];
+static uint64_t next_seq;
+
+static log_level_t console_level = LOG_INFO;
+static log_filter_t filters[LOG_FILTERS];
+
+static const char *level_names[] = { "off", "error", "warn", "info", "debug", "trace" };
+
+const char *log_level_name(log_level_t level)
+{
+    return level <= LOG_TRACE ? level_names[level] : "?";
+}
+
+bool log_parse_level(const char *name, log_level_t *level)
+{
+    for (size_t i = 0; i <= LOG_TRACE; ++i)
+    {
+        if (strcmp(name, level_names[i]) == 0)
+        {
+            *level = i;
+            return true;
+        }
+    }
+    return false;
+}
+
+void log_set_level(log_level_t level)
+{
+    console_level = level;
+}
+
+log_level_t log_get_level(void)
+{
+    return console_level;
+}
+
+static log_filter_t *find_filter(const char *module)
+{
+    for (size_t i = 0; i < LOG_FILTERS; ++i)
+    {
+        if (filters[i].module[0] && strncmp(filters[i].module, module, LOG_MODULE_MAX - 1) == 0)
+        {
+            return &filters[i];
+        }
+    }
+    return NULL;
+}
+
+bool log_set_module_level(const char *module, log_level_t level)
+{
+    log_filter_t *filter = find_filter(module);
+    for (size_t i = 0; !filter && i < LOG_FILTERS; ++i)
+    {
+        if (!filters[i].module[0])
+        {
+            filter = &filters[i];
+        }
+    }
+    if (!filter)
+    {
+        return false;
+    }
+
+    ksnprintf(filter->module, LOG_MODULE_MAX, "%s", module);
+    filter->level = level;
+    return true;
+}
+
+void log_clear_module_level(const char *module)
+{
+    log_filter_t *filter = find_filter(module);
+    if (filter)
+    {
+        filter->module[0] = '\0';
+    }
+}
+
+static bool log_enabled(log_level_t level, const char *module)
+{
+    log_filter_t *filter = find_filter(module);
+    return level <= (filter ? filter->level : console_level);
+}
+
+bool log_configure(const char *spec)
+{
+    bool ok = true;
+    while (*spec)
+    {
+        char item[LOG_MODULE_MAX + 8];
+        size_t len = 0;
+        while (spec[len] && spec[len] != ',')
+        {
+            ++len;
+        }
+        ksnprintf(item, len < sizeof(item) ? len + 1 : sizeof(item), "%s", spec);
+        spec += spec[len] ? len + 1 : len;
+
+        log_level_t level;
+        char *colon = item;
+        while (*colon && *colon != ':')
+        {
+            ++colon;
+        }
+        if (!*colon)
+        {
+            if (log_parse_level(item, &level))
+            {
+                log_set_level(level);
+            }
+            else
+            {
+                ok = false;
+            }
+            continue;
+        }
+
+        *colon = '\0';
+        if (!log_parse_level(colon + 1, &level) || !log_set_module_level(item, level))
+        {
+            ok = false;
+        }
+    }
+    return ok;
+}
+
+void log_init(void)
+{
+    char spec[CMDLINE_MAX];
+    log_level_t level;
+
+    if (cmdline_get("loglevel", spec, sizeof(spec)) && log_parse_level(spec, &level))
+    {
+        log_set_level(level);
+    }
+    if (cmdline_get("log", spec, sizeof(spec)) && !log_configure(spec))
+    {
+        klog(LOG_WARN, "log", "Ignored invalid parts of log=%s", spec);
+    }
+}
+
+static void print_record(const log_record_t *record)
+{
+    uint64_t us = record->timestamp_ns / NS_PER_US;
+    kprintf("[%u.%06u] %s: %s\n", (unsigned int) (us / 1000000), (unsigned int) (us % 1000000), record->module, record->message);
+}
+
+void klog(log_level_t level, const char *module, const char *format, ...)
+{
+    uint64_t now = time_now_ns();
+
+    bool irq = arch_irq_save();
+    log_record_t *record = &records[next_seq % LOG_RECORDS];
+    record->seq = next_seq++;
+    record->timestamp_ns = now;
+    record->level = level;
+    ksnprintf(record->module, LOG_MODULE_MAX, "%s", module);
+
+    va_list parameters;
+    va_start(parameters, format);
+    kvsnprintf(record->message, LOG_MESSAGE_MAX, format, parameters);
+    va_end(parameters);
+
+    if (log_enabled(level, record->module))
+    {
+        print_record(record);
+    }
+    arch_irq_restore(irq);
+}
+
+uint64_t log_first_seq(void)
+{
+    return next_seq > LOG_RECORDS ? next_seq - LOG_RECORDS : 0;
+}
+
+uint64_t log_next_seq(void)
+{
+    return next_seq;
+}
+
+bool log_get(uint64_t seq, log_record_t *record)
+{
+    bool irq = arch_irq_save();
+    bool found = seq >= log_first_seq() && seq < next_seq;
+    if (found)
+    {
+        *record = records[seq % LOG_RECORDS];
+    }
+    arch_irq_restore(irq);
+    return found;
+}
+
+void log_dump(void)
+{
+    log_record_t record;
+    for (uint64_t seq = log_first_seq(); seq < log_next_seq(); ++seq)
+    {
+        if (log_get(seq, &record))
+        {
+            print_record(&record);
+        }
+    }
+}
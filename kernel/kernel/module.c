@@ -0,0 +1,81 @@
+#include <stddef.h>
+#include <stdint.h>
+
+#include <libk/io.h>
+#include <libk/string.h>
+#include <log.h>
+#include <module.h>
+
+static boot_module_t modules[MODULE_MAX];
+static size_t num_modules;
+
+// GRUB passes "path args..." as the module string, split it into the two
+static void parse_string(boot_module_t *module, const char *str)
+{
+    size_t len = 0;
+    while (str[len] && str[len] != ' ')
+    {
+        ++len;
+    }
+    ksnprintf(module->name, len + 1 < MODULE_NAME_MAX ? len + 1 : MODULE_NAME_MAX, "%s", str);
+
+    str += len;
+    while (*str == ' ')
+    {
+        ++str;
+    }
+    module->args = str;
+}
+
+void module_init(const multiboot_info_t *mbi)
+{
+    if (!mbi || !(mbi->flags & MULTIBOOT_INFO_MODS))
+    {
+        return;
+    }
+
+    const multiboot_module_t *mods = (const multiboot_module_t*) mbi->mods_addr;
+    for (uint32_t i = 0; i < mbi->mods_count && num_modules < MODULE_MAX; ++i)
+    {
+        boot_module_t *module = &modules[num_modules++];
+        parse_string(module, mods[i].string ? (const char*) mods[i].string : "");
+        module->data = (const uint8_t*) mods[i].mod_start;
+        module->size = mods[i].mod_end - mods[i].mod_start;
+        klog(LOG_INFO, "module", "%s: %u bytes at %08x", module->name, (unsigned int) module->size, mods[i].mod_start);
+    }
+}
+
+size_t module_count(void)
+{
+    return num_modules;
+}
+
+const boot_module_t *module_get(size_t index)
+{
+    return index < num_modules ? &modules[index] : NULL;
+}
+
+static const char *basename(const char *path)
+{
+    const char *base = path;
+    for (; *path; ++path)
+    {
+        if (*path == '/')
+        {
+            base = path + 1;
+        }
+    }
+    return base;
+}
+
+const boot_module_t *module_find(const char *name)
+{
+    for (size_t i = 0; i < num_modules; ++i)
+    {
+        if (strcmp(modules[i].name, name) == 0 || strcmp(basename(modules[i].name), name) == 0)
+        {
+            return &modules[i];
+        }
+    }
+    return NULL;
+}
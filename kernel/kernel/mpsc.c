@@ -0,0 +1,100 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <cpu.h>
+#include <sync/mpsc.h>
+
+// Each slot's sequence number says whose turn it is: equal to the position when it is free for
+// the producer claiming that position, position + 1 once it holds a value for the consumer
+void mpsc_init(mpsc_queue_t *queue, mpsc_slot_t *slots, size_t capacity)
+{
+    queue->slots = slots;
+    queue->mask = capacity - 1;
+    queue->head = 0;
+    queue->tail = 0;
+    for (size_t i = 0; i < capacity; ++i)
+    {
+        __atomic_store_n(&slots[i].seq, i, __ATOMIC_RELAXED);
+    }
+}
+
+bool mpsc_push(mpsc_queue_t *queue, uintptr_t value)
+{
+    uint32_t pos = __atomic_load_n(&queue->head, __ATOMIC_RELAXED);
+    while (true)
+    {
+        mpsc_slot_t *slot = &queue->slots[pos & queue->mask];
+        int32_t diff = (int32_t) (__atomic_load_n(&slot->seq, __ATOMIC_ACQUIRE) - pos);
+        if (diff == 0)
+        {
+            // Claim the position, on failure pos is reloaded with the current head
+            if (__atomic_compare_exchange_n(&queue->head, &pos, pos + 1, true, __ATOMIC_RELAXED, __ATOMIC_RELAXED))
+            {
+                slot->value = value;
+                __atomic_store_n(&slot->seq, pos + 1, __ATOMIC_RELEASE);
+                return true;
+            }
+        }
+        else if (diff < 0)
+        {
+            // The consumer hasn't freed this slot from the previous lap yet
+            return false;
+        }
+        else
+        {
+            pos = __atomic_load_n(&queue->head, __ATOMIC_RELAXED);
+        }
+    }
+}
+
+bool mpsc_pop(mpsc_queue_t *queue, uintptr_t *value)
+{
+    uint32_t pos = queue->tail;
+    mpsc_slot_t *slot = &queue->slots[pos & queue->mask];
+    if (__atomic_load_n(&slot->seq, __ATOMIC_ACQUIRE) != pos + 1)
+    {
+        return false;
+    }
+
+    *value = slot->value;
+    queue->tail = pos + 1;
+    __atomic_store_n(&slot->seq, pos + queue->mask + 1, __ATOMIC_RELEASE);
+    return true;
+}
+
+uintptr_t mpsc_pop_wait(mpsc_queue_t *queue)
+{
+    uintptr_t value;
+    while (!mpsc_pop(queue, &value))
+    {
+        arch_idle();
+    }
+    return value;
+}
+
+#ifdef KERNEL_TEST
+#include <test.h>
+
+TEST_CASE(mpsc_keeps_order_and_bounds)
+{
+    mpsc_slot_t slots[4];
+    mpsc_queue_t queue;
+    uintptr_t value;
+
+    mpsc_init(&queue, slots, 4);
+    TEST_ASSERT(!mpsc_pop(&queue, &value));
+    for (uintptr_t i = 0; i < 4; ++i)
+    {
+        TEST_ASSERT(mpsc_push(&queue, i));
+    }
+    TEST_ASSERT(!mpsc_push(&queue, 4));
+
+    // Wrap around a few laps
+    for (uintptr_t i = 0; i < 12; ++i)
+    {
+        TEST_ASSERT(mpsc_pop(&queue, &value) && value == i);
+        TEST_ASSERT(mpsc_push(&queue, i + 4));
+    }
+}
+#endif
@@ -0,0 +1,64 @@
+#include <stdarg.h>
+#include <stdbool.h>
+
+#include <cpu.h>
+#include <crashdump.h>
+#include <drivers/serial/uart.h>
+#include <libk/io.h>
+#include <log.h>
+#include <panic.h>
+#include <tty/tty.h>
+
+#define PANIC_SERIAL_PORT 0 // COM1
+#define PANIC_MESSAGE_MAX 160
+
+static bool panicking;
+
+__attribute__((noreturn)) static void panic_screen(const void *frame, const char *format, va_list parameters)
+{
+    arch_disable_interrupts();
+    // Faulting again while drawing the screen, there is nothing more useful to do
+    if (panicking)
+    {
+        arch_halt();
+    }
+    panicking = true;
+
+    static char message[PANIC_MESSAGE_MAX];
+    kvsnprintf(message, sizeof(message), format, parameters);
+    klog(LOG_ERROR, "panic", "%s", message);
+
+    // The timer won't flush the console any more
+    tty_set_autoflush(true);
+#ifndef GDBSTUB
+    if (kprintf_mirror_port() < 0 && serial_present(PANIC_SERIAL_PORT))
+    {
+        kprintf_mirror_serial(PANIC_SERIAL_PORT);
+    }
+#endif
+
+    // White on red, cleared, cursor home
+    kprintf("\x1b[0;97;41m\x1b[2J\x1b[H");
+    kprintf("KERNEL PANIC\n\n");
+    kprintf("%s\n\n", message);
+    arch_dump_registers(frame);
+    kprintf("\nBacktrace:\n");
+    arch_backtrace(frame);
+
+    crashdump_write(frame, message);
+    arch_halt();
+}
+
+void panic(const char *format, ...)
+{
+    va_list parameters;
+    va_start(parameters, format);
+    panic_screen(NULL, format, parameters);
+}
+
+void panic_frame(const void *frame, const char *format, ...)
+{
+    va_list parameters;
+    va_start(parameters, format);
+    panic_screen(frame, format, parameters);
+}
@@ -0,0 +1,172 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <cpu.h>
+#include <dev/device.h>
+#include <libk/string.h>
+#include <rand.h>
+
+#define CHACHA_KEY_WORDS 8
+#define CHACHA_BLOCK_WORDS 16
+#define CHACHA_BLOCK_SIZE (CHACHA_BLOCK_WORDS * sizeof(uint32_t))
+#define CHACHA_ROUNDS 20
+
+#define JITTER_SAMPLES 64
+
+#define ROTL(x, n) (((x) << (n)) | ((x) >> (32 - (n))))
+
+#define QUARTER_ROUND(a, b, c, d) \
+    a += b; d ^= a; d = ROTL(d, 16); \
+    c += d; b ^= c; b = ROTL(b, 12); \
+    a += b; d ^= a; d = ROTL(d, 8); \
+    c += d; b ^= c; b = ROTL(b, 7);
+
+// "expand 32-byte k"
+static const uint32_t chacha_constants[4] = { 0x61707865, 0x3320646E, 0x79622D32, 0x6B206574 };
+
+static uint32_t key[CHACHA_KEY_WORDS];
+static uint64_t counter;
+
+static int random_dev_read(device_t *dev, uint64_t offset, void *buf, size_t len);
+static int random_dev_write(device_t *dev, uint64_t offset, const void *buf, size_t len);
+
+static device_t random_device =
+{
+    .name = "random",
+    .type = DEVICE_CHAR,
+    .read = random_dev_read,
+    .write = random_dev_write,
+};
+
+static void chacha_block(uint32_t *out)
+{
+    uint32_t state[CHACHA_BLOCK_WORDS];
+    memcpy(state, chacha_constants, sizeof(chacha_constants));
+    memcpy(state + 4, key, sizeof(key));
+    state[12] = counter & 0xFFFFFFFF;
+    state[13] = counter >> 32;
+    state[14] = 0;
+    state[15] = 0;
+    ++counter;
+
+    memcpy(out, state, sizeof(state));
+    for (int i = 0; i < CHACHA_ROUNDS; i += 2)
+    {
+        QUARTER_ROUND(out[0], out[4], out[8], out[12]);
+        QUARTER_ROUND(out[1], out[5], out[9], out[13]);
+        QUARTER_ROUND(out[2], out[6], out[10], out[14]);
+        QUARTER_ROUND(out[3], out[7], out[11], out[15]);
+        QUARTER_ROUND(out[0], out[5], out[10], out[15]);
+        QUARTER_ROUND(out[1], out[6], out[11], out[12]);
+        QUARTER_ROUND(out[2], out[7], out[8], out[13]);
+        QUARTER_ROUND(out[3], out[4], out[9], out[14]);
+    }
+
+    for (int i = 0; i < CHACHA_BLOCK_WORDS; ++i)
+    {
+        out[i] += state[i];
+    }
+}
+
+// Replace the key with fresh output so earlier output can't be recovered from the state
+static void rekey(void)
+{
+    uint32_t block[CHACHA_BLOCK_WORDS];
+    chacha_block(block);
+    memcpy(key, block, sizeof(key));
+    memset(block, 0, sizeof(block));
+}
+
+// Fallback when the CPU has no RNG: fold the timing noise of a short busy loop
+static uint32_t jitter_entropy(void)
+{
+    uint32_t value = 0;
+    for (int i = 0; i < JITTER_SAMPLES; ++i)
+    {
+        uint64_t start = arch_cycles();
+        for (volatile int j = 0; j < (i & 7) + 1; ++j)
+        {
+        }
+        uint32_t delta = arch_cycles() - start;
+        value = ROTL(value, 7) ^ delta;
+    }
+    return value;
+}
+
+static uint32_t seed_word(void)
+{
+    uint32_t value;
+    if (arch_random_seed(&value) || arch_random(&value))
+    {
+        return value ^ (uint32_t) arch_cycles();
+    }
+    return jitter_entropy();
+}
+
+void rand_init(void)
+{
+    for (int i = 0; i < CHACHA_KEY_WORDS; ++i)
+    {
+        key[i] = seed_word();
+    }
+    counter = 0;
+    rekey();
+
+    device_register(&random_device);
+}
+
+void rand_mix(const void *data, size_t len)
+{
+    const uint8_t *bytes = (const uint8_t*) data;
+    uint8_t *key_bytes = (uint8_t*) key;
+    for (size_t i = 0; i < len; ++i)
+    {
+        key_bytes[i % sizeof(key)] ^= bytes[i];
+        if (i % sizeof(key) == sizeof(key) - 1)
+        {
+            rekey();
+        }
+    }
+    rekey();
+}
+
+void rand_fill(void *buf, size_t len)
+{
+    uint8_t *out = (uint8_t*) buf;
+    uint32_t block[CHACHA_BLOCK_WORDS];
+    while (len > 0)
+    {
+        chacha_block(block);
+        size_t amount = len < CHACHA_BLOCK_SIZE ? len : CHACHA_BLOCK_SIZE;
+        memcpy(out, block, amount);
+        out += amount;
+        len -= amount;
+    }
+    memset(block, 0, sizeof(block));
+    rekey();
+}
+
+static int random_dev_read(device_t *dev, uint64_t offset, void *buf, size_t len)
+{
+    (void) dev;
+    (void) offset;
+    rand_fill(buf, len);
+    return len;
+}
+
+// Writing to the random device stirs the data into the pool
+static int random_dev_write(device_t *dev, uint64_t offset, const void *buf, size_t len)
+{
+    (void) dev;
+    (void) offset;
+    rand_mix(buf, len);
+    return len;
+}
+
+uint32_t rand_u32(void)
+{
+    uint32_t value;
+    rand_fill(&value, sizeof(value));
+    return value;
+}
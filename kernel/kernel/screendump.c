@@ -0,0 +1,78 @@
+#include <stddef.h>
+#include <stdint.h>
+
+#include <drivers/serial/uart.h>
+#include <drivers/video/cp437.h>
+#include <libk/io.h>
+#include <libk/string.h>
+#include <tty/screendump.h>
+#include <tty/tty.h>
+
+static void dump_line(int port, const char *str)
+{
+    serial_write(port, str, strlen(str));
+    serial_write(port, "\r\n", 2);
+}
+
+static void dump_utf8(int port, uint32_t codepoint)
+{
+    char buf[3];
+    size_t len;
+    // The code page never goes past the BMP, three bytes is enough
+    if (codepoint < 0x80)
+    {
+        buf[0] = codepoint;
+        len = 1;
+    }
+    else if (codepoint < 0x800)
+    {
+        buf[0] = 0xC0 | codepoint >> 6;
+        buf[1] = 0x80 | (codepoint & 0x3F);
+        len = 2;
+    }
+    else
+    {
+        buf[0] = 0xE0 | codepoint >> 12;
+        buf[1] = 0x80 | (codepoint >> 6 & 0x3F);
+        buf[2] = 0x80 | (codepoint & 0x3F);
+        len = 3;
+    }
+    serial_write(port, buf, len);
+}
+
+void screendump(int serial_port)
+{
+    static const char hex[] = "0123456789abcdef";
+    size_t width = tty_width();
+    size_t height = tty_height();
+    char header[48];
+
+    ksnprintf(header, sizeof(header), "-----BEGIN SCREEN %ux%u-----", (unsigned int) width, (unsigned int) height);
+    dump_line(serial_port, header);
+    for (size_t row = 0; row < height; ++row)
+    {
+        size_t end = width;
+        while (end > 0 && cp437_to_unicode(tty_get_cell(row, end - 1) & 0xFF) == ' ')
+        {
+            --end;
+        }
+        for (size_t col = 0; col < end; ++col)
+        {
+            dump_utf8(serial_port, cp437_to_unicode(tty_get_cell(row, col) & 0xFF));
+        }
+        serial_write(serial_port, "\r\n", 2);
+    }
+
+    dump_line(serial_port, "-----ATTRIBUTES-----");
+    for (size_t row = 0; row < height; ++row)
+    {
+        for (size_t col = 0; col < width; ++col)
+        {
+            uint8_t attr = tty_get_cell(row, col) >> 8;
+            char digits[2] = { hex[attr >> 4], hex[attr & 0xF] };
+            serial_write(serial_port, digits, 2);
+        }
+        serial_write(serial_port, "\r\n", 2);
+    }
+    dump_line(serial_port, "-----END SCREEN-----");
+}
@@ -0,0 +1,370 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <cpu.h>
+#include <cpu/interrupts.h>
+#include <dev/device.h>
+#include <drivers/block/bcache.h>
+#include <libk/io.h>
+#include <libk/string.h>
+#include <log.h>
+#include <module.h>
+#include <shell.h>
+#include <time/time.h>
+#include <tty/screendump.h>
+
+#define SHELL_PROMPT "> "
+#define DUMP_DEFAULT_LEN 64
+#define DUMP_BYTES_PER_LINE 16
+
+typedef struct shell_command_t
+{
+    const char *name;
+    const char *usage;
+    void (*run)(int argc, char **argv);
+} shell_command_t;
+
+static const multiboot_info_t *boot_info;
+static int shell_port;
+
+static bool parse_number(const char *str, uint32_t *value)
+{
+    uint32_t base = 10;
+    if (str[0] == '0' && (str[1] == 'x' || str[1] == 'X'))
+    {
+        base = 16;
+        str += 2;
+    }
+    if (!*str)
+    {
+        return false;
+    }
+
+    uint32_t result = 0;
+    for (; *str; ++str)
+    {
+        uint32_t digit;
+        if (*str >= '0' && *str <= '9')
+        {
+            digit = *str - '0';
+        }
+        else if (*str >= 'a' && *str <= 'f')
+        {
+            digit = *str - 'a' + 10;
+        }
+        else if (*str >= 'A' && *str <= 'F')
+        {
+            digit = *str - 'A' + 10;
+        }
+        else
+        {
+            return false;
+        }
+        if (digit >= base)
+        {
+            return false;
+        }
+        result = result * base + digit;
+    }
+    *value = result;
+    return true;
+}
+
+static void cmd_help(int argc, char **argv);
+
+static void cmd_mem(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    if (!boot_info || !(boot_info->flags & MULTIBOOT_INFO_MEMORY))
+    {
+        kprintf("No memory information from the boot loader\n");
+        return;
+    }
+    kprintf("Lower memory: %u KiB, upper memory: %u KiB\n", boot_info->mem_lower, boot_info->mem_upper);
+
+    if (!(boot_info->flags & MULTIBOOT_INFO_MEM_MAP))
+    {
+        return;
+    }
+    uintptr_t addr = boot_info->mmap_addr;
+    uintptr_t end = addr + boot_info->mmap_length;
+    while (addr < end)
+    {
+        const multiboot_mmap_entry_t *entry = (const multiboot_mmap_entry_t*) addr;
+        kprintf("  %08x - %08x %s\n", (uint32_t) entry->addr, (uint32_t) (entry->addr + entry->len - 1),
+            entry->type == MULTIBOOT_MEMORY_AVAILABLE ? "available" : "reserved");
+        addr += entry->size + sizeof(entry->size);
+    }
+}
+
+static void cmd_dump(int argc, char **argv)
+{
+    uint32_t addr;
+    uint32_t len = DUMP_DEFAULT_LEN;
+    if (argc < 2 || !parse_number(argv[1], &addr) || (argc > 2 && !parse_number(argv[2], &len)))
+    {
+        kprintf("usage: dump <addr> [len]\n");
+        return;
+    }
+
+    const uint8_t *bytes = (const uint8_t*) (uintptr_t) addr;
+    for (uint32_t line = 0; line < len; line += DUMP_BYTES_PER_LINE)
+    {
+        char ascii[DUMP_BYTES_PER_LINE + 1];
+        uint32_t count = len - line < DUMP_BYTES_PER_LINE ? len - line : DUMP_BYTES_PER_LINE;
+
+        kprintf("%08x:", addr + line);
+        for (uint32_t i = 0; i < count; ++i)
+        {
+            uint8_t byte = bytes[line + i];
+            // Format with a leading 1 to get the zero padding, then skip it
+            char hex[4];
+            kprintf(" %s", utoa(byte + 0x100, hex, 16) + 1);
+            ascii[i] = byte >= ' ' && byte < 0x7F ? byte : '.';
+        }
+        ascii[count] = '\0';
+        for (uint32_t i = count; i < DUMP_BYTES_PER_LINE; ++i)
+        {
+            kprintf("   ");
+        }
+        kprintf("  %s\n", ascii);
+    }
+}
+
+static void cmd_dmesg(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    log_dump();
+}
+
+static void cmd_log(int argc, char **argv)
+{
+    if (argc < 2)
+    {
+        kprintf("Console log level: %s\n", log_level_name(log_get_level()));
+        return;
+    }
+    if (!log_configure(argv[1]))
+    {
+        kprintf("Invalid log spec %s\n", argv[1]);
+    }
+}
+
+static void cmd_devices(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    for (size_t i = 0; i < device_count(); ++i)
+    {
+        device_t *dev = device_get(i);
+        kprintf("%s (%s)\n", dev->name, dev->type == DEVICE_BLOCK ? "block" : "char");
+    }
+}
+
+static void cmd_irqs(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    kprintf("IRQ      count   avg cyc   max cyc  spurious unhandled owner\n");
+    for (uint8_t irq = 0; irq < IRQ_COUNT; ++irq)
+    {
+        interrupt_stats_t stats;
+        interrupt_get_stats(IRQ_VECTOR(irq), &stats);
+        const char *owner = irq_owner(irq);
+        uint32_t spurious = irq_spurious_count(irq);
+        uint32_t unhandled = irq_unhandled_count(irq);
+        if (!owner && stats.count == 0 && spurious == 0 && unhandled == 0)
+        {
+            continue;
+        }
+
+        unsigned int avg = stats.count ? stats.total_cycles / stats.count : 0;
+        kprintf("%3u %10u %9u %9u %9u %9u %s\n", irq, (unsigned int) stats.count, avg, (unsigned int) stats.max_cycles, spurious, unhandled, owner ? owner : "-");
+    }
+}
+
+static void cmd_modules(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    for (size_t i = 0; i < module_count(); ++i)
+    {
+        const boot_module_t *module = module_get(i);
+        kprintf("%s: %u bytes at %08x %s\n", module->name, (unsigned int) module->size, (uintptr_t) module->data, module->args);
+    }
+}
+
+static void cmd_cat(int argc, char **argv)
+{
+    if (argc < 2)
+    {
+        kprintf("usage: cat <module>\n");
+        return;
+    }
+
+    const boot_module_t *module = module_find(argv[1]);
+    if (!module)
+    {
+        kprintf("No module named %s\n", argv[1]);
+        return;
+    }
+    kwrite((const char*) module->data, module->size);
+}
+
+static void cmd_screendump(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    screendump(shell_port);
+}
+
+static void cmd_sync(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    if (!bcache_sync(NULL))
+    {
+        kprintf("Failed to write back some cached sectors\n");
+    }
+}
+
+static void cmd_uptime(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    uint64_t ms = time_now_ns() / NS_PER_MS;
+    kprintf("Up %u.%03u s\n", (unsigned int) (ms / 1000), (unsigned int) (ms % 1000));
+}
+
+static void cmd_date(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    date_t date;
+    if (!time_local_date(&date))
+    {
+        kprintf("Wall clock unknown\n");
+        return;
+    }
+
+    int offset = time_tz_offset();
+    unsigned int abs_offset = offset < 0 ? -offset : offset;
+    kprintf("%04u-%02u-%02u %02u:%02u:%02u %c%02u%02u\n", date.year, date.month, date.day, date.hour, date.minute, date.second, offset < 0 ? '-' : '+', abs_offset / 60, abs_offset % 60);
+}
+
+static void cmd_reboot(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    arch_reboot();
+}
+
+static const shell_command_t commands[] =
+{
+    { "help", "list commands", cmd_help },
+    { "mem", "show the boot loader's memory map", cmd_mem },
+    { "dump", "<addr> [len] hex dump memory", cmd_dump },
+    { "dmesg", "print the kernel log", cmd_dmesg },
+    { "log", "[spec] show or set log filters, e.g. debug,ata:off", cmd_log },
+    { "devices", "list registered devices", cmd_devices },
+    { "irqs", "show interrupt line owners and counters", cmd_irqs },
+    { "modules", "list modules loaded by the boot loader", cmd_modules },
+    { "cat", "<module> print a boot module's contents", cmd_cat },
+    { "screendump", "send the console contents over serial", cmd_screendump },
+    { "sync", "write back cached disk sectors", cmd_sync },
+    { "uptime", "time since boot", cmd_uptime },
+    { "date", "show the wall clock time", cmd_date },
+    { "reboot", "reset the machine", cmd_reboot },
+};
+
+#define COMMAND_COUNT (sizeof(commands) / sizeof(commands[0]))
+
+static void cmd_help(int argc, char **argv)
+{
+    (void) argc;
+    (void) argv;
+    for (size_t i = 0; i < COMMAND_COUNT; ++i)
+    {
+        kprintf("%s - %s\n", commands[i].name, commands[i].usage);
+    }
+}
+
+// Split line in place on spaces, returning the number of arguments
+static int split_args(char *line, char **argv)
+{
+    int argc = 0;
+    while (*line && argc < SHELL_MAX_ARGS)
+    {
+        while (*line == ' ' || *line == '\t')
+        {
+            *line++ = '\0';
+        }
+        if (!*line)
+        {
+            break;
+        }
+        argv[argc++] = line;
+        while (*line && *line != ' ' && *line != '\t')
+        {
+            ++line;
+        }
+    }
+    return argc;
+}
+
+static void run_line(char *line)
+{
+    char *argv[SHELL_MAX_ARGS];
+    int argc = split_args(line, argv);
+    if (argc == 0)
+    {
+        return;
+    }
+
+    for (size_t i = 0; i < COMMAND_COUNT; ++i)
+    {
+        if (strcmp(argv[0], commands[i].name) == 0)
+        {
+            commands[i].run(argc, argv);
+            return;
+        }
+    }
+    kprintf("Unknown command %s, try help\n", argv[0]);
+}
+
+void shell_run(ldisc_t *ldisc, int serial_port, const multiboot_info_t *mbi)
+{
+    char line[SHELL_LINE_MAX];
+    boot_info = mbi;
+    shell_port = serial_port;
+
+    while (true)
+    {
+        kprintf(SHELL_PROMPT);
+        size_t len = ldisc_read(ldisc, line, sizeof(line) - 1);
+        if (len == 0)
+        {
+            kprintf("\n");
+            continue;
+        }
+        if (line[len - 1] != '\n' && len == sizeof(line) - 1)
+        {
+            // Running the rest of the line as its own command could do anything, throw it away
+            while (len > 0 && line[len - 1] != '\n')
+            {
+                len = ldisc_read(ldisc, line, sizeof(line) - 1);
+            }
+            kprintf("line too long\n");
+            continue;
+        }
+        if (line[len - 1] == '\n')
+        {
+            --len;
+        }
+        line[len] = '\0';
+        run_line(line);
+    }
+}
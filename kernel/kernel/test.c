@@ -0,0 +1,48 @@
+#ifdef KERNEL_TEST
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#include <cpu.h>
+#include <libk/io.h>
+#include <test.h>
+
+#define TEST_SERIAL_PORT 0 // COM1
+
+extern const test_case_t test_cases_start[];
+extern const test_case_t test_cases_end[];
+
+static bool current_failed;
+
+void test_fail(const char *file, int line, const char *cond)
+{
+    current_failed = true;
+    kprintf("\n    %s:%d: assertion failed: %s\n", file, line, cond);
+}
+
+void test_run_all(void)
+{
+    size_t count = test_cases_end - test_cases_start;
+    size_t failed = 0;
+
+    kprintf_mirror_serial(TEST_SERIAL_PORT);
+    kprintf("running %u tests\n", (unsigned int) count);
+    for (const test_case_t *test = test_cases_start; test < test_cases_end; ++test)
+    {
+        kprintf("test %s ... ", test->name);
+        current_failed = false;
+        test->func();
+        if (current_failed)
+        {
+            ++failed;
+        }
+        kprintf("%s\n", current_failed ? "FAILED" : "ok");
+    }
+    kprintf("\ntest result: %s. %u passed; %u failed\n", failed ? "FAILED" : "ok",
+        (unsigned int) (count - failed), (unsigned int) failed);
+
+    arch_qemu_exit(failed ? TEST_EXIT_FAILURE : TEST_EXIT_SUCCESS);
+    arch_halt();
+}
+
+#endif
@@ -0,0 +1,332 @@
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <cmdline.h>
+#include <cpu.h>
+#include <drivers/timer/pit.h>
+#include <drivers/timer/rtc.h>
+#include <libk/string.h>
+#include <log.h>
+#include <sync/seqlock.h>
+#include <time/time.h>
+#include <time/timer.h>
+
+// How often the software clock is checked against the RTC
+#define RTC_SYNC_MS (60 * 1000)
+
+static bool use_pv_clock;
+static uint64_t boot_ns;
+static uint64_t boot_cycles;
+static uint64_t cycles_hz;
+
+// Wall clock time at the moment time_now_ns was 0, stepped by the RTC sync timer
+static seqlock_t wall_lock = SEQLOCK_INIT;
+static bool wall_valid;
+static uint64_t wall_boot_ns;
+static timer_t rtc_sync_timer;
+
+static int tz_offset_min;
+
+// Accepts "UTC", "+hh", "-hhmm" or "+hh:mm"
+static bool parse_tz(const char *spec, int *minutes)
+{
+    if (strcmp(spec, "UTC") == 0)
+    {
+        *minutes = 0;
+        return true;
+    }
+
+    int sign;
+    if (*spec == '+')
+    {
+        sign = 1;
+    }
+    else if (*spec == '-')
+    {
+        sign = -1;
+    }
+    else
+    {
+        return false;
+    }
+    ++spec;
+
+    int digits[4];
+    int count = 0;
+    for (; *spec && count < 4; ++spec)
+    {
+        if (*spec == ':' && count == 2)
+        {
+            continue;
+        }
+        if (*spec < '0' || *spec > '9')
+        {
+            return false;
+        }
+        digits[count++] = *spec - '0';
+    }
+    if (*spec || (count != 2 && count != 4))
+    {
+        return false;
+    }
+
+    int hours = digits[0] * 10 + digits[1];
+    int mins = count == 4 ? digits[2] * 10 + digits[3] : 0;
+    if (hours > 14 || mins > 59)
+    {
+        return false;
+    }
+    *minutes = sign * (hours * 60 + mins);
+    return true;
+}
+
+static bool read_rtc_ns(uint64_t *ns)
+{
+    date_t date;
+    if (!rtc_read(&date))
+    {
+        return false;
+    }
+    *ns = time_from_date(&date) * NS_PER_SEC;
+    return true;
+}
+
+// The RTC only counts whole seconds, so only step the clock once it is clearly off.
+// Runs from the timer interrupt, so a sample caught mid-update is skipped rather than waited out.
+static void rtc_sync(timer_t *timer, void *data)
+{
+    (void) timer;
+    (void) data;
+    date_t date;
+    if (!rtc_try_read(&date))
+    {
+        return;
+    }
+    uint64_t rtc_ns = time_from_date(&date) * NS_PER_SEC;
+
+    uint64_t now = time_now_ns();
+    int64_t drift = (int64_t) (wall_boot_ns + now - rtc_ns);
+    if (drift > -(int64_t) NS_PER_SEC && drift < 2 * (int64_t) NS_PER_SEC)
+    {
+        return;
+    }
+
+    seqlock_write_begin(&wall_lock);
+    wall_boot_ns = rtc_ns - now;
+    seqlock_write_end(&wall_lock);
+    klog(LOG_DEBUG, "time", "Stepped wall clock by %d ms to match the RTC", (int) (-drift / (int64_t) NS_PER_MS));
+}
+
+void time_init(void)
+{
+    cycles_hz = arch_cycles_frequency();
+    boot_cycles = arch_cycles();
+    use_pv_clock = arch_pv_clock(&boot_ns);
+
+    uint64_t rtc_ns;
+    if (read_rtc_ns(&rtc_ns))
+    {
+        wall_boot_ns = rtc_ns - time_now_ns();
+        wall_valid = true;
+        timer_setup(&rtc_sync_timer, rtc_sync, NULL);
+        timer_periodic(&rtc_sync_timer, RTC_SYNC_MS);
+    }
+
+    char tz[16];
+    if (cmdline_get("tz", tz, sizeof(tz)) && !parse_tz(tz, &tz_offset_min))
+    {
+        klog(LOG_WARN, "time", "Ignoring invalid timezone %s", tz);
+    }
+}
+
+const char *time_source(void)
+{
+    return use_pv_clock ? "kvmclock" : "tsc";
+}
+
+// Nanoseconds since time_init
+uint64_t time_now_ns(void)
+{
+    uint64_t ns;
+    if (use_pv_clock && arch_pv_clock(&ns))
+    {
+        return ns - boot_ns;
+    }
+
+    if (cycles_hz == 0)
+    {
+        return 0;
+    }
+
+    uint64_t cycles = arch_cycles() - boot_cycles;
+    return (cycles / cycles_hz) * NS_PER_SEC + (cycles % cycles_hz) * NS_PER_SEC / cycles_hz;
+}
+
+bool time_wall_ns(uint64_t *ns)
+{
+    if (!wall_valid)
+    {
+        return false;
+    }
+
+    uint32_t seq;
+    uint64_t base;
+    do
+    {
+        seq = seqlock_read_begin(&wall_lock);
+        base = wall_boot_ns;
+    } while (seqlock_read_retry(&wall_lock, seq));
+
+    *ns = base + time_now_ns();
+    return true;
+}
+
+int time_tz_offset(void)
+{
+    return tz_offset_min;
+}
+
+bool time_local_date(date_t *date)
+{
+    uint64_t ns;
+    if (!time_wall_ns(&ns))
+    {
+        return false;
+    }
+    time_to_date(ns / NS_PER_SEC + (int64_t) tz_offset_min * 60, date);
+    return true;
+}
+
+// Without a calibrated cycle counter, count down on PIT channel 2 instead
+// Rounded up so a delay is never shorter than asked for, split to keep ns * hz from overflowing
+static uint64_t ns_to_ticks(uint64_t ns, uint64_t hz)
+{
+    return (ns / NS_PER_SEC) * hz + ((ns % NS_PER_SEC) * hz + NS_PER_SEC - 1) / NS_PER_SEC;
+}
+
+static void pit_delay(uint64_t ns)
+{
+    uint64_t counts = ns_to_ticks(ns, PIT_FREQUENCY);
+    while (counts > 0)
+    {
+        uint16_t chunk = counts > UINT16_MAX ? UINT16_MAX : counts;
+        pit_countdown_start(chunk);
+        while (!pit_countdown_done())
+        {
+            arch_pause();
+        }
+        pit_countdown_stop();
+        counts -= chunk;
+    }
+}
+
+static void delay(uint64_t ns)
+{
+    uint64_t hz = arch_cycles_frequency();
+    if (hz == 0)
+    {
+        pit_delay(ns);
+        return;
+    }
+
+    uint64_t cycles = ns_to_ticks(ns, hz);
+    uint64_t start = arch_cycles();
+    while (arch_cycles() - start < cycles)
+    {
+        arch_pause();
+    }
+}
+
+void ndelay(uint32_t ns)
+{
+    delay(ns);
+}
+
+void udelay(uint32_t us)
+{
+    delay((uint64_t) us * NS_PER_US);
+}
+
+void mdelay(uint32_t ms)
+{
+    delay((uint64_t) ms * NS_PER_MS);
+}
+
+// Day counting from Howard Hinnant's date algorithms, with March as the first month of the year
+// so the leap day falls at its end
+static uint32_t days_from_civil(int year, int month, int day)
+{
+    year -= month <= 2;
+    uint32_t era = year / 400;
+    uint32_t yoe = year - era * 400;
+    uint32_t doy = (153 * (month + (month > 2 ? -3 : 9)) + 2) / 5 + day - 1;
+    uint32_t doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    return era * 146097 + doe - 719468;
+}
+
+uint64_t time_from_date(const date_t *date)
+{
+    uint64_t days = days_from_civil(date->year, date->month, date->day);
+    return days * 86400 + date->hour * 3600 + date->minute * 60 + date->second;
+}
+
+void time_to_date(uint64_t seconds, date_t *date)
+{
+    uint32_t days = seconds / 86400;
+    uint32_t rem = seconds % 86400;
+    date->hour = rem / 3600;
+    date->minute = rem / 60 % 60;
+    date->second = rem % 60;
+
+    uint32_t z = days + 719468;
+    uint32_t era = z / 146097;
+    uint32_t doe = z - era * 146097;
+    uint32_t yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    uint32_t doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    uint32_t mp = (5 * doy + 2) / 153;
+    date->day = doy - (153 * mp + 2) / 5 + 1;
+    date->month = mp < 10 ? mp + 3 : mp - 9;
+    date->year = yoe + era * 400 + (date->month <= 2);
+}
+
+#ifdef KERNEL_TEST
+#include <test.h>
+
+TEST_CASE(time_date_round_trip)
+{
+    date_t epoch = { 1970, 1, 1, 0, 0, 0 };
+    date_t leap = { 2024, 2, 29, 23, 59, 58 };
+    date_t date;
+
+    TEST_ASSERT(time_from_date(&epoch) == 0);
+    TEST_ASSERT(time_from_date(&leap) == 1709251198);
+    time_to_date(1709251198, &date);
+    TEST_ASSERT(date.year == 2024 && date.month == 2 && date.day == 29);
+    TEST_ASSERT(date.hour == 23 && date.minute == 59 && date.second == 58);
+    time_to_date(951782400, &date);
+    TEST_ASSERT(date.year == 2000 && date.month == 2 && date.day == 29);
+}
+
+TEST_CASE(time_parse_tz)
+{
+    int minutes;
+
+    TEST_ASSERT(parse_tz("UTC", &minutes) && minutes == 0);
+    TEST_ASSERT(parse_tz("+05:30", &minutes) && minutes == 330);
+    TEST_ASSERT(parse_tz("-0800", &minutes) && minutes == -480);
+    TEST_ASSERT(parse_tz("+01", &minutes) && minutes == 60);
+    TEST_ASSERT(!parse_tz("0100", &minutes));
+    TEST_ASSERT(!parse_tz("+1:00", &minutes));
+    TEST_ASSERT(!parse_tz("+05:60", &minutes));
+}
+
+TEST_CASE(time_delay_ticks_round_up_without_overflow)
+{
+    TEST_ASSERT(ns_to_ticks(0, 3000000000ULL) == 0);
+    TEST_ASSERT(ns_to_ticks(1, 3000000000ULL) == 3);
+    TEST_ASSERT(ns_to_ticks(1, PIT_FREQUENCY) == 1);
+    TEST_ASSERT(ns_to_ticks(NS_PER_SEC, PIT_FREQUENCY) == PIT_FREQUENCY);
+    TEST_ASSERT(ns_to_ticks(7000 * NS_PER_MS, 3000000000ULL) == 21000000000ULL);
+    TEST_ASSERT(ns_to_ticks(UINT32_MAX * NS_PER_MS, 3000000000ULL) == (uint64_t) UINT32_MAX * 3000000);
+}
+#endif
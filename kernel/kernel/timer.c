@@ -0,0 +1,376 @@
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include <cpu.h>
+#include <drivers/timer/pit.h>
+#include <time/timer.h>
+
+// A hierarchical wheel: the first level has a slot per tick for the next 256 ticks, each further
+// level has 64 slots each covering a whole lap of the level below. Timers move down a level
+// ("cascade") when the level below wraps around to their slot.
+#define ROOT_BITS 8
+#define LEVEL_BITS 6
+#define ROOT_SIZE (1 << ROOT_BITS)
+#define LEVEL_SIZE (1 << LEVEL_BITS)
+#define LEVELS 3
+#define MAX_DELTA ((1ULL << (ROOT_BITS + LEVELS * LEVEL_BITS)) - 1)
+
+static timer_t *root[ROOT_SIZE];
+static timer_t *levels[LEVELS][LEVEL_SIZE];
+
+// Next tick the wheel will process
+static uint64_t wheel_ticks;
+
+static void list_add(timer_t **head, timer_t *timer)
+{
+    timer->next = *head;
+    timer->pprev = head;
+    if (*head)
+    {
+        (*head)->pprev = &timer->next;
+    }
+    *head = timer;
+}
+
+static void list_remove(timer_t *timer)
+{
+    *timer->pprev = timer->next;
+    if (timer->next)
+    {
+        timer->next->pprev = timer->pprev;
+    }
+    timer->next = NULL;
+    timer->pprev = NULL;
+}
+
+static void wheel_add(timer_t *timer)
+{
+    // Timers already due go into the slot processed next
+    if (timer->expires < wheel_ticks)
+    {
+        timer->expires = wheel_ticks;
+    }
+    if (timer->expires - wheel_ticks > MAX_DELTA)
+    {
+        timer->expires = wheel_ticks + MAX_DELTA;
+    }
+
+    uint64_t delta = timer->expires - wheel_ticks;
+    if (delta < ROOT_SIZE)
+    {
+        list_add(&root[timer->expires & (ROOT_SIZE - 1)], timer);
+        return;
+    }
+
+    for (int level = 0; level < LEVELS; ++level)
+    {
+        int shift = ROOT_BITS + (level + 1) * LEVEL_BITS;
+        if (level == LEVELS - 1 || delta < (1ULL << shift))
+        {
+            int slot = (timer->expires >> (shift - LEVEL_BITS)) & (LEVEL_SIZE - 1);
+            list_add(&levels[level][slot], timer);
+            return;
+        }
+    }
+}
+
+// Re-sort one slot of a level into the levels below, returns the slot index that was cascaded
+static int cascade(int level)
+{
+    int slot = (wheel_ticks >> (ROOT_BITS + level * LEVEL_BITS)) & (LEVEL_SIZE - 1);
+    timer_t *timer = levels[level][slot];
+    levels[level][slot] = NULL;
+    while (timer)
+    {
+        timer_t *next = timer->next;
+        wheel_add(timer);
+        timer = next;
+    }
+    return slot;
+}
+
+static void run_tick(void)
+{
+    int index = wheel_ticks & (ROOT_SIZE - 1);
+    // The root wrapped, pull the next lap down from the levels above
+    for (int level = 0; index == 0 && level < LEVELS; ++level)
+    {
+        if (cascade(level) != 0)
+        {
+            break;
+        }
+    }
+
+    timer_t *expired = root[index];
+    root[index] = NULL;
+    if (expired)
+    {
+        expired->pprev = &expired;
+    }
+    ++wheel_ticks;
+
+    while (expired)
+    {
+        timer_t *timer = expired;
+        list_remove(timer);
+        if (timer->period)
+        {
+            timer->expires += timer->period;
+            wheel_add(timer);
+        }
+        timer->callback(timer, timer->data);
+    }
+}
+
+static void timer_tick(uint64_t ticks)
+{
+    while (wheel_ticks <= ticks)
+    {
+        run_tick();
+    }
+}
+
+static bool running;
+
+bool timer_init(void)
+{
+    wheel_ticks = pit_ticks() + 1;
+    running = pit_register_tick(timer_tick);
+    return running;
+}
+
+bool timer_running(void)
+{
+    return running;
+}
+
+static uint32_t ms_to_ticks(uint32_t ms)
+{
+    uint32_t ticks = (uint64_t) ms * PIT_TICK_HZ / 1000;
+    return ticks ? ticks : 1;
+}
+
+void timer_setup(timer_t *timer, timer_callback_t callback, void *data)
+{
+    timer->next = NULL;
+    timer->pprev = NULL;
+    timer->callback = callback;
+    timer->data = data;
+    timer->period = 0;
+}
+
+static void arm(timer_t *timer, uint32_t ms, bool periodic)
+{
+    bool irq = arch_irq_save();
+    if (timer->pprev)
+    {
+        list_remove(timer);
+    }
+    timer->period = periodic ? ms_to_ticks(ms) : 0;
+    timer->expires = pit_ticks() + ms_to_ticks(ms);
+    wheel_add(timer);
+    arch_irq_restore(irq);
+}
+
+void timer_oneshot(timer_t *timer, uint32_t ms)
+{
+    arm(timer, ms, false);
+}
+
+void timer_periodic(timer_t *timer, uint32_t ms)
+{
+    arm(timer, ms, true);
+}
+
+void timer_cancel(timer_t *timer)
+{
+    bool irq = arch_irq_save();
+    if (timer->pprev)
+    {
+        list_remove(timer);
+    }
+    timer->period = 0;
+    arch_irq_restore(irq);
+}
+
+bool timer_pending(const timer_t *timer)
+{
+    return timer->pprev != NULL;
+}
+
+#ifdef KERNEL_TEST
+#include <libk/string.h>
+#include <test.h>
+
+typedef struct test_record_t
+{
+    uint32_t count;
+    uint64_t first_tick;
+    uint64_t last_tick;
+    timer_t *cancel;
+} test_record_t;
+
+static timer_t *saved_root[ROOT_SIZE];
+static timer_t *saved_levels[LEVELS][LEVEL_SIZE];
+static uint64_t saved_ticks;
+static bool saved_irq;
+
+// Drive a private, empty wheel by hand with the PIT tick kept out
+static void test_wheel_begin(uint64_t start)
+{
+    saved_irq = arch_irq_save();
+    memcpy(saved_root, root, sizeof(root));
+    memcpy(saved_levels, levels, sizeof(levels));
+    saved_ticks = wheel_ticks;
+    memset(root, 0, sizeof(root));
+    memset(levels, 0, sizeof(levels));
+    wheel_ticks = start;
+}
+
+static void test_wheel_end(void)
+{
+    memcpy(root, saved_root, sizeof(root));
+    memcpy(levels, saved_levels, sizeof(levels));
+    wheel_ticks = saved_ticks;
+    arch_irq_restore(saved_irq);
+}
+
+static void test_run_until(uint64_t tick)
+{
+    while (wheel_ticks <= tick)
+    {
+        run_tick();
+    }
+}
+
+static void test_callback(timer_t *timer, void *data)
+{
+    (void) timer;
+    test_record_t *record = data;
+    // wheel_ticks has already moved past the tick being run
+    uint64_t now = wheel_ticks - 1;
+    if (record->count++ == 0)
+    {
+        record->first_tick = now;
+    }
+    record->last_tick = now;
+    if (record->cancel)
+    {
+        timer_cancel(record->cancel);
+    }
+}
+
+// Like arm, but relative to the wheel instead of the PIT
+static void test_arm(timer_t *timer, test_record_t *record, uint64_t delta, uint32_t period)
+{
+    timer_setup(timer, test_callback, record);
+    timer->period = period;
+    timer->expires = wheel_ticks + delta;
+    wheel_add(timer);
+}
+
+#define TEST_ONESHOTS 9
+
+static const uint64_t oneshot_deltas[TEST_ONESHOTS] =
+{
+    // Before, at and after the root wrap, one lap of level 0, level 1 and level 2
+    1, 55, 56, 57, ROOT_SIZE + 44, 5000, (1 << 14) + 5, 70000, (1 << 20) + 7
+};
+
+static void wheel_oneshots(void)
+{
+    timer_t timers[TEST_ONESHOTS];
+    test_record_t records[TEST_ONESHOTS];
+    uint64_t start = wheel_ticks;
+
+    memset(records, 0, sizeof(records));
+    for (int i = 0; i < TEST_ONESHOTS; ++i)
+    {
+        test_arm(&timers[i], &records[i], oneshot_deltas[i], 0);
+    }
+
+    test_run_until(start + oneshot_deltas[TEST_ONESHOTS - 1] + ROOT_SIZE);
+    for (int i = 0; i < TEST_ONESHOTS; ++i)
+    {
+        TEST_ASSERT(records[i].count == 1);
+        TEST_ASSERT(records[i].first_tick == start + oneshot_deltas[i]);
+        TEST_ASSERT(!timer_pending(&timers[i]));
+    }
+}
+
+TEST_CASE(timer_oneshots_fire_on_time_across_cascades)
+{
+    test_wheel_begin(ROOT_SIZE - 56);
+    wheel_oneshots();
+    test_wheel_end();
+}
+
+static void wheel_periodic(void)
+{
+    timer_t timer;
+    test_record_t record;
+    uint64_t start = wheel_ticks;
+
+    memset(&record, 0, sizeof(record));
+    test_arm(&timer, &record, 100, 100);
+    test_run_until(start + 1050);
+    TEST_ASSERT(record.count == 10);
+    TEST_ASSERT(record.first_tick == start + 100);
+    TEST_ASSERT(record.last_tick == start + 1000);
+    TEST_ASSERT(timer_pending(&timer));
+
+    timer_cancel(&timer);
+    test_run_until(start + 1200);
+    TEST_ASSERT(record.count == 10);
+    TEST_ASSERT(!timer_pending(&timer));
+}
+
+TEST_CASE(timer_periodic_rearms)
+{
+    test_wheel_begin(250);
+    wheel_periodic();
+    test_wheel_end();
+}
+
+static void wheel_cancel_from_callback(void)
+{
+    timer_t first;
+    timer_t same_tick;
+    timer_t later;
+    test_record_t first_record;
+    test_record_t same_tick_record;
+    test_record_t later_record;
+    uint64_t start = wheel_ticks;
+
+    memset(&first_record, 0, sizeof(first_record));
+    memset(&same_tick_record, 0, sizeof(same_tick_record));
+    memset(&later_record, 0, sizeof(later_record));
+
+    // Slots run newest first, so first runs before same_tick and takes it out of the expired list
+    test_arm(&same_tick, &same_tick_record, 10, 0);
+    test_arm(&later, &later_record, 20, 0);
+    test_arm(&first, &first_record, 10, 0);
+    first_record.cancel = &same_tick;
+
+    test_run_until(start + 10);
+    TEST_ASSERT(first_record.count == 1);
+    TEST_ASSERT(same_tick_record.count == 0);
+    TEST_ASSERT(!timer_pending(&same_tick));
+
+    // And a timer in another slot
+    first_record.cancel = &later;
+    test_arm(&first, &first_record, 5, 0);
+    test_run_until(start + 30);
+    TEST_ASSERT(first_record.count == 2);
+    TEST_ASSERT(later_record.count == 0);
+    TEST_ASSERT(!timer_pending(&later));
+}
+
+TEST_CASE(timer_cancel_from_callback)
+{
+    test_wheel_begin(1000);
+    wheel_cancel_from_callback();
+    test_wheel_end();
+}
+#endif
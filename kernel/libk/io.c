@@ -1,20 +1,49 @@
 #include <stdarg.h>
+#include <stdbool.h>
 #include <stddef.h>
 
+#include <drivers/serial/uart.h>
 #include <libk/io.h>
 #include <libk/string.h>
 #include <tty/tty.h>
 
+static int serial_mirror = -1;
+
 static void kprint(const char *str, size_t len)
 {
     tty_write(str, len);
+    if (serial_mirror >= 0)
+    {
+        for (size_t i = 0; i < len; ++i)
+        {
+            if (str[i] == '\n')
+            {
+                serial_putc(serial_mirror, '\r');
+            }
+            serial_putc(serial_mirror, str[i]);
+        }
+    }
 }
 
-void kprintf(const char *format, ...)
+void kwrite(const char *str, size_t len)
 {
-    va_list parameters;
-    va_start(parameters, format);
+    kprint(str, len);
+}
+
+void kprintf_mirror_serial(int port)
+{
+    serial_mirror = port;
+}
+
+int kprintf_mirror_port(void)
+{
+    return serial_mirror;
+}
 
+typedef void (*kformat_out_t)(void *ctx, const char *str, size_t len);
+
+static int kformat(kformat_out_t out, void *ctx, const char *format, va_list parameters)
+{
     int written = 0;
     while (*format != '\0')
     {
@@ -29,57 +58,135 @@ void kprintf(const char *format, ...)
             {
                 ++amount;
             }
-            kprint(format, amount * 1);
+            out(ctx, format, amount * 1);
             format += amount;
             written += amount;
             continue;
         }
 
         const char *format_begun_at = format++;
+        bool zero_pad = *format == '0';
+        size_t width = 0;
+        while (*format >= '0' && *format <= '9')
+        {
+            width = width * 10 + (*format++ - '0');
+        }
+
+        char buf[50];
+        const char *str = buf;
         if (*format == 'c')
         {
-            ++format;
-            char c = (char) va_arg(parameters, int);
-            kprint(&c, 1);
-            ++written;
+            buf[0] = (char) va_arg(parameters, int);
+            buf[1] = '\0';
         }
         else if (*format == 's')
         {
-            ++format;
-            const char *str = va_arg(parameters, const char*);
-            size_t len = strlen(str);
-            kprint(str, len);
-            written += len;
+            str = va_arg(parameters, const char*);
         }
         else if (*format == 'd')
         {
-            ++format;
-            int i = va_arg(parameters, int);
-            char buf[50];
-            itoa(i, buf, 10);
-            size_t len = strlen(buf);
-            kprint(buf, len);
-            written += len;
+            itoa(va_arg(parameters, int), buf, 10);
+        }
+        else if (*format == 'u')
+        {
+            utoa(va_arg(parameters, unsigned int), buf, 10);
         }
         else if (*format == 'x')
         {
-            ++format;
-            int i = va_arg(parameters, int);
-            char buf[50];
-            buf[0] = '0';
-            buf[1] = 'x';
-            itoa(i, buf + 2, 16);
-            size_t len = strlen(buf);
-            kprint(buf, len);
-            written += len;
+            out(ctx, "0x", 2);
+            written += 2;
+            utoa(va_arg(parameters, unsigned int), buf, 16);
         }
         else
         {
             format = format_begun_at;
             size_t len = strlen(format);
-            kprint(format, len);
+            out(ctx, format, len);
             written += len;
             format += len;
+            continue;
+        }
+        // A %c of '\0' still writes one character
+        size_t len = *format == 'c' ? 1 : strlen(str);
+        ++format;
+
+        for (size_t i = len; i < width; ++i)
+        {
+            out(ctx, zero_pad ? "0" : " ", 1);
+            ++written;
         }
+        out(ctx, str, len);
+        written += len;
     }
-}
\ No newline at end of file
+    return written;
+}
+
+static void kprint_out(void *ctx, const char *str, size_t len)
+{
+    (void) ctx;
+    kprint(str, len);
+}
+
+typedef struct kbuf_t
+{
+    char *buf;
+    size_t size;
+    size_t len;
+} kbuf_t;
+
+static void kbuf_out(void *ctx, const char *str, size_t len)
+{
+    kbuf_t *kbuf = ctx;
+    for (size_t i = 0; i < len && kbuf->len + 1 < kbuf->size; ++i)
+    {
+        kbuf->buf[kbuf->len++] = str[i];
+    }
+}
+
+void kprintf(const char *format, ...)
+{
+    va_list parameters;
+    va_start(parameters, format);
+    kformat(kprint_out, NULL, format, parameters);
+    va_end(parameters);
+}
+
+void kvprintf(const char *format, va_list parameters)
+{
+    kformat(kprint_out, NULL, format, parameters);
+}
+
+int kvsnprintf(char *buf, size_t size, const char *format, va_list parameters)
+{
+    kbuf_t kbuf = { buf, size, 0 };
+    int written = kformat(kbuf_out, &kbuf, format, parameters);
+    if (size > 0)
+    {
+        buf[kbuf.len] = '\0';
+    }
+    return written;
+}
+
+int ksnprintf(char *buf, size_t size, const char *format, ...)
+{
+    va_list parameters;
+    va_start(parameters, format);
+    int written = kvsnprintf(buf, size, format, parameters);
+    va_end(parameters);
+    return written;
+}
+
+#ifdef KERNEL_TEST
+#include <test.h>
+
+TEST_CASE(ksnprintf_formats_and_truncates)
+{
+    char buf[16];
+    TEST_ASSERT(ksnprintf(buf, sizeof(buf), "%d %s %c", -5, "ok", '!') == 7);
+    TEST_ASSERT(strcmp(buf, "-5 ok !") == 0);
+    ksnprintf(buf, sizeof(buf), "%08x|%3u", 0xBEEF, 7u);
+    TEST_ASSERT(strcmp(buf, "0x0000beef|  7") == 0);
+    TEST_ASSERT(ksnprintf(buf, 4, "%s", "truncated") == 9);
+    TEST_ASSERT(strcmp(buf, "tru") == 0);
+}
+#endif
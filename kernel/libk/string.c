@@ -89,6 +89,28 @@ size_t strlen(const char *str)
     return len;
 }
 
+int strcmp(const char *a, const char *b)
+{
+    while (*a && *a == *b)
+    {
+        ++a;
+        ++b;
+    }
+    return (unsigned char) *a - (unsigned char) *b;
+}
+
+int strncmp(const char *a, const char *b, size_t len)
+{
+    for (size_t i = 0; i < len; ++i)
+    {
+        if (a[i] != b[i] || !a[i])
+        {
+            return (unsigned char) a[i] - (unsigned char) b[i];
+        }
+    }
+    return 0;
+}
+
 char *itoa(int num, char *str, int base)
 {
     int i = 0;
@@ -101,7 +123,7 @@ char *itoa(int num, char *str, int base)
         return str;
     }
 
-    if (num < 10 && base == 10)
+    if (num < 0 && base == 10)
     {
         is_negative = true;
         num = -num;
@@ -122,4 +144,57 @@ char *itoa(int num, char *str, int base)
     reverse(str, i);
 
     return str;
-}
\ No newline at end of file
+}
+
+char *utoa(unsigned int num, char *str, int base)
+{
+    int i = 0;
+    do
+    {
+        unsigned int rem = num % base;
+        str[i++] = (rem > 9) ? (rem - 10) + 'a' : rem + '0';
+        num /= base;
+    } while (num != 0);
+
+    str[i] = '\0';
+    reverse(str, i);
+
+    return str;
+}
+
+#ifdef KERNEL_TEST
+#include <test.h>
+
+TEST_CASE(itoa_formats_signed_values)
+{
+    char buf[16];
+    TEST_ASSERT(strcmp(itoa(0, buf, 10), "0") == 0);
+    TEST_ASSERT(strcmp(itoa(7, buf, 10), "7") == 0);
+    TEST_ASSERT(strcmp(itoa(-42, buf, 10), "-42") == 0);
+    TEST_ASSERT(strcmp(itoa(255, buf, 16), "ff") == 0);
+}
+
+TEST_CASE(utoa_formats_full_range)
+{
+    char buf[16];
+    TEST_ASSERT(strcmp(utoa(0, buf, 10), "0") == 0);
+    TEST_ASSERT(strcmp(utoa(4294967295u, buf, 10), "4294967295") == 0);
+    TEST_ASSERT(strcmp(utoa(0xC0000000u, buf, 16), "c0000000") == 0);
+}
+
+TEST_CASE(memmove_handles_overlap)
+{
+    char buf[] = "abcdef";
+    memmove(buf + 2, buf, 4);
+    TEST_ASSERT(memcmp(buf, "ababcd", 6) == 0);
+    memmove(buf, buf + 2, 4);
+    TEST_ASSERT(memcmp(buf, "abcdcd", 6) == 0);
+}
+
+TEST_CASE(strncmp_stops_at_length)
+{
+    TEST_ASSERT(strncmp("console", "consolas", 6) == 0);
+    TEST_ASSERT(strncmp("console", "consolas", 7) != 0);
+    TEST_ASSERT(strncmp("a", "b", 1) < 0);
+}
+#endif